@@ -3,7 +3,7 @@ import os
 import shlex
 import subprocess
 from pathlib import Path
-from typing import Dict, List, Tuple, Optional
+from typing import Dict, List, Tuple
 
 
 # shlex.split() splits according to shell quoting rules.
@@ -30,6 +30,11 @@ def get_wasi_worlds() -> List[str]:
     return ["wasi:cli/command"]
 
 
+def get_unsupported_wasi_proposals() -> List[str]:
+    # jco has no option to provide `wasi:cli/exit#exit-with-code`.
+    return ["cli-exit-with-code"]
+
+
 def get_timeout_seconds() -> float:
     # jco transpiles each component before executing it, so startup can exceed
     # the default runner timeout under concurrent Buck test execution.
@@ -37,17 +42,18 @@ def get_timeout_seconds() -> float:
 
 
 def compute_argv(test_path: str,
-                 args_env_root: Tuple[List[str], Dict[str, str], Optional[str]],
+                 args_env_dirs: Tuple[List[str], Dict[str, str], List[Tuple[Path, str]]],
                  proposals: List[str],
                  wasi_world: str,
                  wasi_version: str) -> List[str]:
-    args, env, root = args_env_root
-    preopens = []
-    if root:
-        preopens.append({
-            "guest": "/",
-            "host": str(root),
-        })
+    args, env, dirs = args_env_dirs
+    if os.name == "nt":
+        # NTFS keeps timestamps in units of 100ns.
+        env = {"TIMESTAMP_GRANULARITY_NS": "100", **env}
+    preopens = [{
+        "guest": guest,
+        "host": str(host),
+    } for host, guest in dirs]
 
     return JCO + [
         "--component", test_path,
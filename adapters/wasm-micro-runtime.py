@@ -3,7 +3,7 @@ import os
 import shlex
 import sys
 from pathlib import Path
-from typing import Dict, List, Tuple, Optional
+from typing import Dict, List, Tuple
 import importlib
 
 
@@ -34,20 +34,20 @@ def get_wasi_worlds() -> List[str]:
 
 
 def compute_argv(test_path: str,
-                 args_env_root: Tuple[List[str], Dict[str, str], Optional[str]],
+                 args_env_dirs: Tuple[List[str], Dict[str, str], List[Tuple[Path, str]]],
                  proposals: List[str],
                  wasi_world: str,
                  wasi_version: str) -> List[str]:
 
     argv = []
     argv += IWASM
-    args, env, root = args_env_root
+    args, env, dirs = args_env_dirs
 
     for k, v in env.items():
         argv += ["--env", f"{k}={v}"]
 
-    if root:
-        argv += [f"--map-dir=/::{root}"]  # noqa: E231
+    for host, guest in dirs:
+        argv += [f"--map-dir={guest}::{host}"]  # noqa: E231
 
     argv += [test_path]
 
@@ -34,20 +34,20 @@ def get_wasi_worlds() -> List[str]:
 
 
 def compute_argv(test_path: str,
-                 args_env_root: Tuple[List[str], Dict[str, str], List[Tuple[Path, str]]],
+                 args_env_dirs: Tuple[List[str], Dict[str, str], List[Tuple[Path, str]]],
                  proposals: List[str],
                  wasi_world: str,
                  wasi_version: str) -> List[str]:
 
     argv = []
     argv += WASMEDGE
-    args, env, root = args_env_root
+    args, env, dirs = args_env_dirs
 
     for k, v in env.items():
         argv += ["--env", f"{k}={v}"]
 
-    if root:
-        argv += ["--dir", f"/:{root}"]
+    for host, guest in dirs:
+        argv += ["--dir", f"{guest}:{host}"]
 
     argv += [test_path]
 
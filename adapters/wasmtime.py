@@ -2,7 +2,7 @@ import os
 import shlex
 import subprocess
 from pathlib import Path
-from typing import Dict, List, Tuple, Optional
+from typing import Dict, List, Tuple
 
 # shlex.split() splits according to shell quoting rules
 WASMTIME = shlex.split(os.getenv("WASMTIME", "wasmtime"), posix=os.name != "nt")
@@ -30,20 +30,27 @@ def get_wasi_worlds() -> List[str]:
 
 
 def compute_argv(test_path: str,
-                 args_env_root: Tuple[List[str], Dict[str, str], Optional[str]],
+                 args_env_dirs: Tuple[List[str], Dict[str, str], List[Tuple[Path, str]]],
                  proposals: List[str],
                  wasi_world: str,
                  wasi_version: str) -> List[str]:
 
     argv = []
     argv += WASMTIME
-    args, env, root = args_env_root
+    args, env, dirs = args_env_dirs
+    if os.name == "nt":
+        # NTFS keeps timestamps in units of 100ns.
+        env = {"TIMESTAMP_GRANULARITY_NS": "100", **env}
 
     for k, v in env.items():
         argv += ["--env", f"{k}={v}"]
 
-    if root:
-        argv += ["--dir", f"{root}::/"]  # noqa: E231
+    for host, guest in dirs:
+        argv += ["--dir", f"{host}::{guest}"]  # noqa: E231
+
+    # Preopened sockets are only implemented by the legacy WASIp1 context.
+    if "TCP_LISTEN_ADDR" in env and wasi_version == "wasm32-wasip1":
+        argv += ["-Spreview2=n", f"-Stcplisten={env['TCP_LISTEN_ADDR']}"]
 
     argv += [test_path]
 
@@ -76,7 +83,9 @@ def _add_wasi_version_options(argv: List[str], wasi_version: str,
             if "http" in proposals:
                 flags_from_proposals += ",http"
             if "sockets" in proposals:
-                flags_from_proposals += ",inherit-network"
+                flags_from_proposals += ",inherit-network,allow-ip-name-lookup"
+            if "cli-exit-with-code" in proposals:
+                flags_from_proposals += ",cli-exit-with-code"
 
             argv[splice_pos:splice_pos] = ["-Wcomponent-model-async",
                                            f"-Sp3{flags_from_proposals}"]
@@ -3,7 +3,7 @@ import os
 import shlex
 import sys
 from pathlib import Path
-from typing import Dict, List, Tuple, Optional
+from typing import Dict, List, Tuple
 import importlib
 
 
@@ -36,7 +36,7 @@ def get_wasi_worlds() -> List[str]:
 
 
 def compute_argv(test_path: str,
-                 args_env_root: Tuple[List[str], Dict[str, str], Optional[str]],
+                 args_env_dirs: Tuple[List[str], Dict[str, str], List[Tuple[Path, str]]],
                  proposals: List[str],
                  wasi_world: str,
                  wasi_version: str) -> List[str]:
@@ -44,13 +44,19 @@ def compute_argv(test_path: str,
     argv = []
     argv += WAZERO
     argv += ["run", "-hostlogging=filesystem"]
-    args, env, root = args_env_root
+    args, env, dirs = args_env_dirs
+    if os.name == "nt":
+        # NTFS keeps timestamps in units of 100ns.
+        env = {"TIMESTAMP_GRANULARITY_NS": "100", **env}
 
     for k, v in env.items():
         argv += [f"-env={k}={v}"]
 
-    if root:
-        argv += [f"-mount={root}:/"]
+    for host, guest in dirs:
+        argv += [f"-mount={host}:{guest}"]
+
+    if "TCP_LISTEN_ADDR" in env:
+        argv += [f"-listen={env['TCP_LISTEN_ADDR']}"]
 
     argv += [test_path]
 
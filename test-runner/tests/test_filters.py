@@ -1,12 +1,14 @@
 from pathlib import Path
+from unittest.mock import patch
 
 from wasi_test_runner.filters import TestExpectationFilter as ExpectationFilter
+from wasi_test_runner.filters import UnsupportedWasiTestExcludeFilter as UnsupportedFilter
 from wasi_test_runner.runtime_adapter import RuntimeMeta
-from wasi_test_runner.test_case import Config, WasiVersion, WasiWorld
+from wasi_test_runner.test_case import Config, Run, Wait, WasiProposal, WasiVersion, WasiWorld
 from wasi_test_runner.test_suite import TestSuiteMeta as SuiteMeta
 
 
-def _meta() -> SuiteMeta:
+def _meta(unsupported_proposals: frozenset[WasiProposal] = frozenset()) -> SuiteMeta:
     return SuiteMeta(
         name="WASI Rust tests [wasm32-wasip3]",
         wasi_version=WasiVersion.WASM32_WASIP3,
@@ -15,6 +17,7 @@ def _meta() -> SuiteMeta:
             version="1.0.0",
             supported_wasi_versions=frozenset([WasiVersion.WASM32_WASIP3]),
             supported_wasi_worlds=frozenset([WasiWorld.CLI_COMMAND]),
+            unsupported_wasi_proposals=unsupported_proposals,
         ),
     )
 
@@ -81,3 +84,35 @@ def test_toml_expectation_filter_marks_expected_fail_as_expected_to_fail(tmp_pat
     # ...but is reported as expected-to-fail.
     assert filt.expected_to_fail(_meta(), "test-name") is True
     assert filt.expected_to_fail(_meta(), "unlisted-test") is False
+
+
+def test_unsupported_filter_skips_test_needing_unsupported_proposal() -> None:
+    meta = _meta(frozenset([WasiProposal.CLI_EXIT_WITH_CODE]))
+    config = Config(proposals=[WasiProposal.CLI_EXIT_WITH_CODE])
+
+    assert UnsupportedFilter().should_skip(meta, "test-name", config) == (
+        True,
+        "WASI proposal unsupported by runtime",
+    )
+
+
+def test_unsupported_filter_runs_test_needing_other_proposals() -> None:
+    meta = _meta(frozenset([WasiProposal.CLI_EXIT_WITH_CODE]))
+    config = Config(proposals=[WasiProposal.HTTP])
+
+    assert UnsupportedFilter().should_skip(meta, "test-name", config) == (False, None)
+    assert UnsupportedFilter().should_skip(_meta(), "test-name", Config(
+        proposals=[WasiProposal.CLI_EXIT_WITH_CODE])) == (False, None)
+
+
+def test_unsupported_filter_skips_tty_test_without_pty() -> None:
+    config = Config(operations=[Run(tty=["stdout"]), Wait()])
+
+    with patch("wasi_test_runner.filters.HAS_PTY", False):
+        assert UnsupportedFilter().should_skip(_meta(), "test-name", config) == (
+            True,
+            "Terminals unsupported on this platform",
+        )
+        assert UnsupportedFilter().should_skip(_meta(), "test-name", Config()) == (False, None)
+    with patch("wasi_test_runner.filters.HAS_PTY", True):
+        assert UnsupportedFilter().should_skip(_meta(), "test-name", config) == (False, None)
@@ -8,7 +8,7 @@ import pytest
 
 from wasi_test_runner.test_case import (
     Config, Failure, Result,
-    Run, Wait, Read, Write, Connect, Send, Recv, Request, Response, Kill,
+    Run, Wait, Read, Write, Connect, Send, Recv, Close, CloseMode, Request, Response, Kill,
     Endpoint, EndpointMode, EndpointResponse, Server, ServerKind,
     ProtocolType, WasiProposal, WasiWorld, TestCaseValidator
 )
@@ -91,6 +91,8 @@ def test_run_from_config_with_defaults() -> None:
     assert run.args == []
     assert run.env == {}
     assert run.root is None
+    assert run.dirs == {}
+    assert run.preopens == []
 
 
 def test_run_from_config_with_values() -> None:
@@ -104,6 +106,73 @@ def test_run_from_config_with_values() -> None:
     assert run.args == ["arg1", "arg2"]
     assert run.env == {"KEY": "value"}
     assert run.root == Path("/test/workdir")
+    assert run.tcplisten is False
+    assert run.reference_time is False
+
+
+def test_run_from_config_with_tcplisten() -> None:
+    run = Run.from_config(Path("/test/path"), {"tcplisten": True})
+
+    assert run.tcplisten is True
+
+
+def test_run_from_config_rejects_non_bool_tcplisten() -> None:
+    with pytest.raises(ValueError, match="tcplisten should be a bool"):
+        Run.from_config(Path("/test/path"), {"tcplisten": "yes"})
+
+
+def test_run_from_config_with_dirs() -> None:
+    config = {
+        "root": "workdir",
+        "dirs": {"/data": "fixtures/a", "cache": "fixtures/b"}
+    }
+    run = Run.from_config(Path("/test/path"), config)
+
+    assert run.dirs == {"/data": Path("/test/fixtures/a"),
+                        "cache": Path("/test/fixtures/b")}
+    assert run.preopens == [(Path("/test/workdir"), "/"),
+                            (Path("/test/fixtures/a"), "/data"),
+                            (Path("/test/fixtures/b"), "cache")]
+
+
+def test_run_from_config_rejects_invalid_dirs() -> None:
+    with pytest.raises(ValueError, match="dirs should be an object"):
+        Run.from_config(Path("/test/path"), {"dirs": ["fixtures/a"]})
+    with pytest.raises(ValueError, match="dirs should be an object"):
+        Run.from_config(Path("/test/path"), {"dirs": {"": "fixtures/a"}})
+    with pytest.raises(ValueError, match="cannot map '/' when root is set"):
+        Run.from_config(Path("/test/path"),
+                        {"root": "workdir", "dirs": {"/": "fixtures/a"}})
+    with pytest.raises(ValueError, match="each map a different directory"):
+        Run.from_config(Path("/test/path"),
+                        {"dirs": {"/data": "fixtures/a", "/cache": "fixtures/a"}})
+    with pytest.raises(ValueError, match="each map a different directory"):
+        Run.from_config(Path("/test/path"),
+                        {"root": "fixtures/a", "dirs": {"/data": "fixtures/a"}})
+
+
+def test_run_from_config_with_reference_time() -> None:
+    run = Run.from_config(Path("/test/path"), {"reference_time": True})
+
+    assert run.reference_time is True
+
+
+def test_run_from_config_rejects_non_bool_reference_time() -> None:
+    with pytest.raises(ValueError, match="reference_time should be a bool"):
+        Run.from_config(Path("/test/path"), {"reference_time": 1})
+
+
+def test_run_from_config_with_tty() -> None:
+    assert Run.from_config(Path("/test/path"), {}).tty == []
+    assert Run.from_config(Path("/test/path"), {"tty": True}).tty == ["stdin", "stdout", "stderr"]
+    assert Run.from_config(Path("/test/path"), {"tty": False}).tty == []
+    assert Run.from_config(Path("/test/path"), {"tty": ["stderr", "stdin"]}).tty == ["stderr", "stdin"]
+
+
+def test_run_from_config_rejects_invalid_tty() -> None:
+    for tty in [1, "stdout", ["stdio"], ["stdout", "stdout"]]:
+        with pytest.raises(ValueError, match="tty should be a bool or a list"):
+            Run.from_config(Path("/test/path"), {"tty": tty})
 
 
 def test_wait_from_config_with_defaults() -> None:
@@ -137,6 +206,8 @@ def test_write_from_config_with_defaults() -> None:
 
     assert write.id == "write"
     assert write.payload == ""
+    assert write.repeat == 1
+    assert write.close is False
 
 
 def test_write_from_config_with_values() -> None:
@@ -146,6 +217,22 @@ def test_write_from_config_with_values() -> None:
     assert write.payload == "input data"
 
 
+def test_write_from_config_with_repeat_and_close() -> None:
+    write = Write.from_config({"id": "stdin", "payload": "ab", "repeat": 3, "close": True})
+
+    assert write.repeat == 3
+    assert write.close is True
+
+
+def test_write_from_config_rejects_invalid_repeat_and_close() -> None:
+    with pytest.raises(ValueError, match="repeat should be a non-negative integer"):
+        Write.from_config({"repeat": -1})
+    with pytest.raises(ValueError, match="repeat should be a non-negative integer"):
+        Write.from_config({"repeat": "3"})
+    with pytest.raises(ValueError, match="close should be a bool"):
+        Write.from_config({"close": "yes"})
+
+
 def test_connect_from_config_with_defaults() -> None:
     connect = Connect.from_config({})
 
@@ -558,6 +645,73 @@ def test_dry_run_write_before_run() -> None:
         validate_config(config)
 
 
+def test_dry_run_write_after_close() -> None:
+    config = Config(operations=[
+        Run(),
+        Write(id="stdin", close=True),
+        Write(id="stdin"),
+        Wait()
+    ])
+    with pytest.raises(AssertionError, match="no such stream: stdin"):
+        validate_config(config)
+
+
+def test_dry_run_read_from_tty() -> None:
+    config = Config(operations=[Run(tty=["stdout"]), Read(id="stdout"), Wait()])
+    with pytest.raises(AssertionError, match="no such stream: stdout"):
+        validate_config(config)
+
+    validate_config(Config(operations=[Run(tty=["stdout"]), Read(id="stderr"), Wait()]))
+
+
+def test_close_from_config_requires_id() -> None:
+    with pytest.raises(ValueError, match="Close operation requires 'id' field"):
+        Close.from_config({})
+
+
+def test_close_from_config_with_values() -> None:
+    assert Close.from_config({"id": "conn1"}) == Close(id="conn1", how=CloseMode.FULL)
+    assert Close.from_config({"id": "conn1", "how": "write"}).how == CloseMode.WRITE
+    assert Close.from_config({"id": "conn1", "how": "reset"}).how == CloseMode.RESET
+    with pytest.raises(ValueError):
+        Close.from_config({"id": "conn1", "how": "read"})
+
+
+def test_dry_run_recv_after_half_close() -> None:
+    validate_config(Config(operations=[
+        Run(),
+        Connect(id="conn1"),
+        Close(id="conn1", how=CloseMode.WRITE),
+        Recv(id="conn1", payload="test"),
+        Close(id="conn1"),
+        Wait()
+    ]))
+
+
+def test_dry_run_send_after_half_close() -> None:
+    config = Config(operations=[
+        Run(),
+        Connect(id="conn1"),
+        Close(id="conn1", how=CloseMode.WRITE),
+        Send(id="conn1", payload="test"),
+        Wait()
+    ])
+    with pytest.raises(AssertionError, match="but got StreamType.HALF_CLOSED_SOCKET: conn1"):
+        validate_config(config)
+
+
+def test_dry_run_recv_after_close() -> None:
+    config = Config(operations=[
+        Run(),
+        Connect(id="conn1"),
+        Close(id="conn1", how=CloseMode.RESET),
+        Recv(id="conn1", payload="test"),
+        Wait()
+    ])
+    with pytest.raises(AssertionError, match="no such stream: conn1"):
+        validate_config(config)
+
+
 def test_dry_run_wait_before_run() -> None:
     config = Config(operations=[Wait()])
     with pytest.raises(AssertionError, match="no process running"):
@@ -570,9 +724,44 @@ def test_dry_run_connect_before_run() -> None:
         validate_config(config)
 
 
-def test_dry_run_connect_with_non_tcp_protocol() -> None:
-    config = Config(operations=[Run(), Connect(protocol_type=ProtocolType.UDP), Wait()])
-    with pytest.raises(AssertionError, match="udp not supported"):
+def test_dry_run_connect_with_http_protocol() -> None:
+    config = Config(operations=[Run(), Connect(protocol_type=ProtocolType.HTTP), Wait()])
+    with pytest.raises(AssertionError, match="http not supported"):
+        validate_config(config)
+
+
+def test_dry_run_udp_connect() -> None:
+    validate_config(Config(operations=[
+        Run(),
+        Connect(id="peer1", protocol_type=ProtocolType.UDP),
+        Connect(id="peer2", protocol_type=ProtocolType.UDP),
+        Send(id="peer1", payload="test"),
+        Recv(id="peer1", payload="test"),
+        Close(id="peer1"),
+        Send(id="peer2", payload="test"),
+        Wait()
+    ]))
+
+
+def test_dry_run_udp_half_close() -> None:
+    config = Config(operations=[
+        Run(),
+        Connect(id="peer1", protocol_type=ProtocolType.UDP),
+        Close(id="peer1", how=CloseMode.WRITE),
+        Wait()
+    ])
+    with pytest.raises(AssertionError, match="but got StreamType.DATAGRAM_SOCKET: peer1"):
+        validate_config(config)
+
+
+def test_dry_run_udp_reset() -> None:
+    config = Config(operations=[
+        Run(),
+        Connect(id="peer1", protocol_type=ProtocolType.UDP),
+        Close(id="peer1", how=CloseMode.RESET),
+        Wait()
+    ])
+    with pytest.raises(AssertionError, match="but got StreamType.DATAGRAM_SOCKET: peer1"):
         validate_config(config)
 
 
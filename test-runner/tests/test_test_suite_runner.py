@@ -1,9 +1,15 @@
+import io
+import os
 import socket
+import sys
 import threading
+import time
 from typing import Any
 from pathlib import Path
 from unittest.mock import ANY, MagicMock, Mock, patch, mock_open
 
+import pytest
+
 import wasi_test_runner.test_suite as ts
 import wasi_test_runner.test_case as tc
 import wasi_test_runner.test_suite_runner as tsr
@@ -166,6 +172,212 @@ def test_runner_should_use_path_for_name_if_manifest_does_not_exist() -> None:
     assert suite.meta.name == "my-path"
 
 
+def test_runner_passes_tcplisten_address_to_runtime() -> None:
+    runtime = Mock()
+    runtime.compute_argv.return_value = ["rt1", "<test>"]
+    config = tc.Config(operations=[tc.Run(tcplisten=True)])
+    runner = tsr.TestCaseRunner(config, "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP1, runtime)
+
+    with patch("subprocess.Popen", return_value=MagicMock()):
+        runner.do_run(tc.Run(tcplisten=True))
+
+    env = runtime.compute_argv.call_args.args[2]
+    host, port = env[tc.TCP_LISTEN_ENV_VAR].split(":")
+    assert host == "127.0.0.1"
+    assert int(port) > 0
+
+
+def test_runner_passes_reference_time_to_runtime() -> None:
+    runtime = Mock()
+    runtime.compute_argv.return_value = ["rt1", "<test>"]
+    config = tc.Config(operations=[tc.Run(reference_time=True)])
+    runner = tsr.TestCaseRunner(config, "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP1, runtime)
+
+    before = time.time_ns()
+    with patch("subprocess.Popen", return_value=MagicMock()):
+        runner.do_run(tc.Run(reference_time=True))
+    after = time.time_ns()
+
+    env = runtime.compute_argv.call_args.args[2]
+    assert before <= int(env[tc.REFERENCE_TIME_ENV_VAR]) <= after
+
+
+def test_runner_sets_runtime_timezone_from_guest_tz() -> None:
+    runtime = Mock()
+    runtime.compute_argv.return_value = ["rt1", "<test>"]
+    run = tc.Run(env={"TZ": "America/New_York"})
+    runner = tsr.TestCaseRunner(tc.Config(operations=[run]), "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP3, runtime)
+
+    with patch("subprocess.Popen", return_value=MagicMock()) as popen:
+        runner.do_run(run)
+
+    assert popen.call_args.kwargs["env"]["TZ"] == "America/New_York"
+    assert runtime.compute_argv.call_args.args[2]["TZ"] == "America/New_York"
+
+
+def test_runner_passes_preopens_to_runtime() -> None:
+    runtime = Mock()
+    runtime.compute_argv.return_value = ["rt1", "<test>"]
+    run = tc.Run(root=Path("root.dir"), dirs={"/data": Path("data.dir")})
+    config = tc.Config(operations=[run])
+    runner = tsr.TestCaseRunner(config, "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP1, runtime)
+
+    with (patch("subprocess.Popen", return_value=MagicMock()),
+          patch("wasi_test_runner.test_suite_runner._cleanup_test_output") as cleanup):
+        runner.do_run(run)
+
+    dirs = runtime.compute_argv.call_args.args[3]
+    assert dirs == [(Path("root.dir"), "/"), (Path("data.dir"), "/data")]
+    assert [c.args[0] for c in cleanup.call_args_list] == [Path("root.dir"), Path("data.dir")]
+
+
+def test_runner_fails_when_runtime_cannot_preopen() -> None:
+    runtime = Mock()
+    runtime.compute_argv.side_effect = ValueError("adapter can only preopen a root directory")
+    run = tc.Run(dirs={"/data": Path("data.dir")})
+    runner = tsr.TestCaseRunner(tc.Config(operations=[run]), "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP1, runtime)
+
+    with (patch("subprocess.Popen") as popen,
+          patch("wasi_test_runner.test_suite_runner._cleanup_test_output")):
+        runner.do_run(run)
+
+    popen.assert_not_called()
+    # pylint: disable-msg=protected-access
+    [failure] = runner._failures
+    assert "can only preopen a root directory" in failure.message
+
+def test_runner_writes_repeated_payload_and_closes_stdin() -> None:
+    runtime = Mock()
+    runtime.compute_argv.return_value = ["rt1", "<test>"]
+    runtime.get_timeout_seconds.return_value = 1
+    config = tc.Config(operations=[tc.Run()])
+    runner = tsr.TestCaseRunner(config, "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP3, runtime)
+    process = MagicMock()
+    process.returncode = 0
+    process.communicate.return_value = ("", "")
+
+    with (patch("subprocess.Popen", return_value=process),
+          patch("wasi_test_runner.test_suite_runner._cleanup_test_output")):
+        runner.do_run(tc.Run())
+        stdin = process.stdin
+        runner.do_write(tc.Write(id="stdin", payload="ab", repeat=3, close=True))
+        runner.do_wait(tc.Wait())
+
+    assert [c.args[0] for c in stdin.write.call_args_list] == ["ab", "ab", "ab"]
+    stdin.close.assert_called_once()
+    # `communicate` must not be handed the closed stream to flush.
+    assert process.stdin is None
+    process.communicate.assert_called_once()
+
+
+@pytest.mark.skipif(os.name != "posix", reason="pseudo-terminals are POSIX only")
+def test_runner_attaches_tty_streams_to_terminal() -> None:
+    # Stands in for a runtime: the exit code has a bit set for each standard
+    # stream that is a terminal.
+    script = ("import sys; print('on the terminal'); "
+              "sys.exit(sum(f.isatty() << i for i, f in enumerate([sys.stdin, sys.stdout, sys.stderr])))")
+    runtime = Mock()
+    runtime.compute_argv.return_value = [sys.executable, "-c", script]
+    runtime.get_timeout_seconds.return_value = 10
+    run = tc.Run(tty=["stdin", "stdout"])
+    runner = tsr.TestCaseRunner(tc.Config(operations=[run]), "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP3, runtime)
+
+    runner.do_run(run)
+    assert not runner.has_failure()
+    runner.do_wait(tc.Wait(exit_code=0b011))
+    runner.do_cleanup(True)
+
+    assert not runner.has_failure()
+
+
+@pytest.mark.skipif(os.name != "posix", reason="pseudo-terminals are POSIX only")
+def test_runner_reports_tty_output_on_failure() -> None:
+    runtime = Mock()
+    runtime.compute_argv.return_value = [sys.executable, "-c", "print('on the terminal')"]
+    runtime.get_timeout_seconds.return_value = 10
+    run = tc.Run(tty=["stdout"])
+    runner = tsr.TestCaseRunner(tc.Config(operations=[run]), "test.wasm",
+                                tc.WasiVersion.WASM32_WASIP3, runtime)
+
+    runner.do_run(run)
+    runner.do_wait(tc.Wait(exit_code=1))
+    runner.do_cleanup(False)
+
+    # pylint: disable-msg=protected-access
+    [failure] = runner._failures
+    assert "==STDOUT==\non the terminal" in failure.message
+
+
+def _runner_connected_to(listener: socket.socket) -> tuple[tsr.TestCaseRunner, socket.socket]:
+    runner = tsr.TestCaseRunner(tc.Config(), "test.wasm", tc.WasiVersion.WASM32_WASIP3, Mock())
+    runner.add_socket("conn", socket.create_connection(listener.getsockname()))
+    peer, _ = listener.accept()
+    return runner, peer
+
+
+def test_runner_half_closes_connection() -> None:
+    with socket.create_server(("127.0.0.1", 0)) as listener:
+        runner, peer = _runner_connected_to(listener)
+        with peer:
+            runner.do_send(tc.Send(id="conn", payload="ping"))
+            runner.do_close(tc.Close(id="conn", how=tc.CloseMode.WRITE))
+            # The peer sees the data, then end-of-stream, and can still reply.
+            assert peer.recv(4) == b"ping"
+            assert peer.recv(4) == b""
+            peer.sendall(b"pong")
+            runner.do_recv(tc.Recv(id="conn", payload="pong"))
+            runner.do_close(tc.Close(id="conn"))
+        runner.do_cleanup(True)
+
+    assert not runner.has_failure()
+
+
+def test_runner_resets_connection() -> None:
+    with socket.create_server(("127.0.0.1", 0)) as listener:
+        runner, peer = _runner_connected_to(listener)
+        with peer:
+            runner.do_close(tc.Close(id="conn", how=tc.CloseMode.RESET))
+            with pytest.raises(ConnectionResetError):
+                peer.recv(4)
+        runner.do_cleanup(True)
+
+    assert not runner.has_failure()
+
+
+def test_runner_udp_connect_sends_from_own_port() -> None:
+    runner = tsr.TestCaseRunner(tc.Config(), "test.wasm", tc.WasiVersion.WASM32_WASIP3, Mock())
+    with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as guest:
+        guest.bind(("127.0.0.1", 0))
+        host, port = guest.getsockname()
+        runner.add_pipe("stdout", io.StringIO(f"{host}:{port}\n{host}:{port}\n"))  # noqa: E231
+        runner.do_connect(tc.Connect(id="a", protocol_type=tc.ProtocolType.UDP))
+        runner.do_connect(tc.Connect(id="b", protocol_type=tc.ProtocolType.UDP))
+
+        senders = []
+        for conn in ["a", "b"]:
+            runner.do_send(tc.Send(id=conn, payload=f"from {conn}"))
+            data, sender = guest.recvfrom(100)
+            assert data == f"from {conn}".encode()
+            senders.append(sender)
+            guest.sendto(data, sender)
+            runner.do_recv(tc.Recv(id=conn, payload=f"from {conn}"))
+        assert senders[0] != senders[1]
+
+        # A datagram is received whole, not just as much of it as expected.
+        guest.sendto(b"longer than expected", senders[0])
+        runner.do_recv(tc.Recv(id="a", payload="longer"))
+        runner.do_cleanup(True)
+
+    assert runner.has_failure()
+
+
 def _serve_one_request(endpoint: tc.Endpoint, request: bytes) -> bytes:
     # The endpoint server is private to the runner; these tests drive it over a
     # real socket because its parsing is the thing under test.
@@ -1,3 +1,4 @@
+import os
 from abc import ABC
 from abc import abstractmethod
 from pathlib import Path
@@ -6,11 +7,14 @@ from typing import Any, NamedTuple, Tuple, Union, Literal
 import tomllib
 
 from .test_suite import TestSuiteMeta
-from .test_case import Config
+from .test_case import Config, Run
 
 SKIP_ACTION = "skip"
 VALID_ACTIONS = [SKIP_ACTION]
 
+# The runner puts `tty` streams on a pseudo-terminal, which only POSIX has.
+HAS_PTY = os.name == "posix"
+
 EXPECTED_PASS = "pass"
 EXPECTED_FAIL = "fail"
 VALID_EXPECTED = [EXPECTED_PASS, EXPECTED_FAIL]
@@ -42,6 +46,10 @@ class UnsupportedWasiTestExcludeFilter(TestFilter):
             return True, "WASI version unsupported by runtime"
         if config.world not in meta.runtime.supported_wasi_worlds:
             return True, "WASI world unsupported by runtime"
+        if any(p in meta.runtime.unsupported_wasi_proposals for p in config.proposals):
+            return True, "WASI proposal unsupported by runtime"
+        if not HAS_PTY and any(isinstance(op, Run) and op.tty for op in config.operations):
+            return True, "Terminals unsupported on this platform"
         return False, None
 
 
@@ -1,10 +1,11 @@
 import importlib.util
+import inspect
 import subprocess
 import sys
 from pathlib import Path
-from typing import NamedTuple, List, Dict, Any, Optional
+from typing import NamedTuple, List, Dict, Any, Tuple
 
-from .test_case import WasiVersion, WasiWorld
+from .test_case import WasiProposal, WasiVersion, WasiWorld
 
 
 class RuntimeMeta(NamedTuple):
@@ -12,6 +13,7 @@ class RuntimeMeta(NamedTuple):
     version: str
     supported_wasi_versions: frozenset[WasiVersion]
     supported_wasi_worlds: frozenset[WasiWorld]
+    unsupported_wasi_proposals: frozenset[WasiProposal] = frozenset()
 
     def __str__(self) -> str:
         return f"{self.name} {self.version}"
@@ -81,10 +83,29 @@ def _get_timeout_seconds(adapter: Any) -> float:
         return 5.0
 
 
+def _get_unsupported_wasi_proposals(adapter: Any) -> frozenset[WasiProposal]:
+    try:
+        return frozenset(
+            WasiProposal(p) for p in adapter.get_unsupported_wasi_proposals()
+        )
+    except AttributeError:
+        return frozenset()
+
+
+def _takes_root_only(adapter: Any) -> bool:
+    # Adapters written before tests could preopen several directories name
+    # the tuple `args_env_root`, and expect the host path to map to `/`, or
+    # None, in place of the list of `(host_path, guest_name)` pairs.
+    params = list(inspect.signature(adapter.compute_argv).parameters)
+    return len(params) > 1 and params[1] == "args_env_root"
+
+
 class RuntimeAdapter:
     def __init__(self, adapter_path: str) -> None:
         _assert_not_legacy_adapter(adapter_path)
+        self._adapter_path = adapter_path
         self._adapter = _load_adapter_as_module(adapter_path)
+        self._root_only = _takes_root_only(self._adapter)
         try:
             name = self._adapter.get_name()
             version = self._adapter.get_version()
@@ -94,12 +115,14 @@ class RuntimeAdapter:
             wasi_worlds = frozenset(
                 WasiWorld(w) for w in self._adapter.get_wasi_worlds()
             )
+            unsupported_proposals = _get_unsupported_wasi_proposals(self._adapter)
             self._timeout_seconds = _get_timeout_seconds(self._adapter)
         except subprocess.CalledProcessError as e:
             raise UnavailableRuntimeAdapterError(adapter_path, e) from e
         except FileNotFoundError as e:
             raise UnavailableRuntimeAdapterError(adapter_path, e) from e
-        self._meta = RuntimeMeta(name, version, wasi_versions, wasi_worlds)
+        self._meta = RuntimeMeta(name, version, wasi_versions, wasi_worlds,
+                                 unsupported_proposals)
 
     def get_meta(self) -> RuntimeMeta:
         return self._meta
@@ -110,7 +133,7 @@ class RuntimeAdapter:
     def compute_argv(self, test_path: str,
                      args: List[str],
                      env: Dict[str, str],
-                     root: Optional[Path],
+                     dirs: List[Tuple[Path, str]],
                      proposals: List[str],
                      wasi_world: WasiWorld,
                      wasi_version: WasiVersion) -> List[str]:
@@ -118,8 +141,14 @@ class RuntimeAdapter:
         # pylint: disable-msg=unknown-option-value
         # pylint: disable-msg=too-many-arguments
         # pylint: disable-msg=too-many-positional-arguments
-        args_env_root = [args, env, root]
-        argv = self._adapter.compute_argv(test_path, args_env_root,
+        args_env_dirs: List[Any] = [args, env, dirs]
+        if self._root_only:
+            if any(guest != "/" for _, guest in dirs):
+                raise ValueError(
+                    f"{self._adapter_path} can only preopen a root directory; "
+                    "its compute_argv should take `args_env_dirs`")
+            args_env_dirs[2] = dirs[0][0] if dirs else None
+        argv = self._adapter.compute_argv(test_path, args_env_dirs,
                                           proposals, wasi_world.value,
                                           wasi_version.value)
         assert isinstance(argv, list)
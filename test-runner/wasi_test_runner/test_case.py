@@ -4,16 +4,16 @@ import re
 import signal
 from pathlib import Path
 from enum import Enum, StrEnum, auto
-from typing import List, NamedTuple, TypeVar, Type, Dict, Any, Set, Optional
+from typing import List, NamedTuple, TypeVar, Type, Dict, Any, Set, Optional, Tuple
 
 # Top level configuration keys
-LEGACY_CONFIG_KEYS = {"args", "root", "env", "exit_code", "stderr", "stdout"}
+LEGACY_CONFIG_KEYS = {"args", "root", "dirs", "env", "exit_code", "stderr", "stdout"}
 CONFIG_KEYS = {"operations", "proposals", "world", "servers"}
 
 
 # Supported operations.
 SUPPORTED_OPERATIONS = {"run", "wait", "read", "write", "connect",
-                        "send", "recv", "request", "kill"}
+                        "send", "recv", "close", "request", "kill"}
 
 # Supported http methods.
 HTTP_METHODS = {"GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"}
@@ -27,6 +27,19 @@ TRAILER_HEADER_PREFIX = "x-trailer-"
 # Server names travel to the guest inside an environment variable name.
 SERVER_NAME_RE = re.compile(r"[A-Za-z0-9_]+")
 
+# Environment variable carrying the `<host>:<port>` of a `tcplisten` socket.
+TCP_LISTEN_ENV_VAR = "TCP_LISTEN_ADDR"
+
+# Environment variable carrying the host's realtime clock, in nanoseconds since
+# the Unix epoch, for `reference_time` runs.
+REFERENCE_TIME_ENV_VAR = "REFERENCE_TIME_NS"
+
+# Environment variable which, set for the guest, also sets the runtime's timezone.
+TZ_ENV_VAR = "TZ"
+
+# Names of the guest's standard streams, in file descriptor order.
+STDIO_STREAMS = ["stdin", "stdout", "stderr"]
+
 
 class EndpointMode(StrEnum):
     # Answer with the endpoint's configured `response`.
@@ -119,6 +132,20 @@ class Run(NamedTuple):
     args: List[str] = []
     env: Dict[str, str] = {}
     root: Optional[Path] = None
+    # Further preopened directories, keyed by their name in the guest.
+    dirs: Dict[str, Path] = {}
+    # Whether the runtime should preopen a listening TCP socket for the guest.
+    tcplisten: bool = False
+    # Whether the guest is told the host's current time, for clock tests.
+    reference_time: bool = False
+    # Standard streams the guest gets on a pseudo-terminal instead of a pipe.
+    tty: List[str] = []
+
+    @property
+    def preopens(self) -> List[Tuple[Path, str]]:
+        # (host, guest) pairs in the order the runtime should preopen them.
+        preopens = [(self.root, "/")] if self.root else []
+        return preopens + [(host, guest) for guest, host in self.dirs.items()]
 
     @classmethod
     def from_config(cls: Type[R], test_config_path: Path, config: Dict[str, Any]) -> R:
@@ -126,13 +153,46 @@ class Run(NamedTuple):
         root = config.get("root", default.root)
         if root:
             root = test_config_path.parent / root
+        dirs = config.get("dirs", default.dirs)
+        if not isinstance(dirs, dict) or not all(
+                isinstance(guest, str) and guest and isinstance(host, str)
+                for guest, host in dirs.items()):
+            raise ValueError("Run dirs should be an object of guest name to host path")
+        if root and "/" in dirs:
+            raise ValueError("Run dirs cannot map '/' when root is set")
+        hosts = [root] if root else []
+        hosts += [test_config_path.parent / host for host in dirs.values()]
+        if len(set(hosts)) != len(hosts):
+            # Preopens are distinct objects to the guest, so they can't share
+            # a host directory.
+            raise ValueError("Run dirs should each map a different directory")
+        tcplisten = config.get("tcplisten", default.tcplisten)
+        if not isinstance(tcplisten, bool):
+            raise ValueError("Run tcplisten should be a bool")
+        reference_time = config.get("reference_time", default.reference_time)
+        if not isinstance(reference_time, bool):
+            raise ValueError("Run reference_time should be a bool")
         return cls(
             args=config.get("args", default.args),
             env=config.get("env", default.env),
-            root=root
+            root=root,
+            dirs={guest: test_config_path.parent / host for guest, host in dirs.items()},
+            tcplisten=tcplisten,
+            reference_time=reference_time,
+            tty=_tty_from_config(config.get("tty", default.tty))
         )
 
 
+def _tty_from_config(tty: Any) -> List[str]:
+    # `true` puts all of the standard streams on the terminal.
+    if isinstance(tty, bool):
+        return list(STDIO_STREAMS) if tty else []
+    if not isinstance(tty, list) or not all(name in STDIO_STREAMS for name in tty) \
+            or len(set(tty)) != len(tty):
+        raise ValueError(f"Run tty should be a bool or a list of distinct names from {STDIO_STREAMS}")
+    return tty
+
+
 W = TypeVar("W", bound="Wait")
 
 
@@ -181,6 +241,32 @@ class Recv(NamedTuple):
         )
 
 
+class CloseMode(StrEnum):
+    # Stop sending, like `shutdown(SHUT_WR)`; receiving still works.
+    WRITE = 'write'
+    # Orderly close of both directions.
+    FULL = 'full'
+    # Abortive close, which resets the connection.
+    RESET = 'reset'
+
+
+Cl = TypeVar("Cl", bound="Close")
+
+
+class Close(NamedTuple):
+    id: str
+    how: CloseMode = CloseMode.FULL
+
+    @classmethod
+    def from_config(cls: Type[Cl], config: Dict[str, Any]) -> Cl:
+        if "id" not in config:
+            raise ValueError("Close operation requires 'id' field")
+        return cls(
+            id=config["id"],
+            how=CloseMode(config.get("how", CloseMode.FULL))
+        )
+
+
 Rx = TypeVar("Rx", bound="Read")
 
 
@@ -203,13 +289,25 @@ Wr = TypeVar("Wr", bound="Write")
 class Write(NamedTuple):
     id: str = "write"
     payload: str = ""
+    # How many times to write `payload`, for inputs too large to spell out.
+    repeat: int = 1
+    # Whether to close the stream afterwards, so the guest sees end-of-file.
+    close: bool = False
 
     @classmethod
     def from_config(cls: Type[Wr], config: Dict[str, Any]) -> Wr:
         default = cls()
+        repeat = config.get("repeat", default.repeat)
+        if not isinstance(repeat, int) or isinstance(repeat, bool) or repeat < 0:
+            raise ValueError("Write repeat should be a non-negative integer")
+        close = config.get("close", default.close)
+        if not isinstance(close, bool):
+            raise ValueError("Write close should be a bool")
         return cls(
             id=config.get("id", default.id),
-            payload=config.get("payload", default.payload)
+            payload=config.get("payload", default.payload),
+            repeat=repeat,
+            close=close
         )
 
 
@@ -438,12 +536,13 @@ class Kill(NamedTuple):
         return cls(signal.Signals[signame])
 
 
-Operation = Run | Wait | Read | Write | Connect | Send | Recv | Request | Kill
+Operation = Run | Wait | Read | Write | Connect | Send | Recv | Close | Request | Kill
 
 
 class WasiProposal(StrEnum):
     HTTP = 'http'
     SOCKETS = 'sockets'
+    CLI_EXIT_WITH_CODE = 'cli-exit-with-code'
 
 
 T = TypeVar("T", bound="Config")
@@ -568,6 +667,8 @@ class Config(NamedTuple):
                     operations.append(Send.from_config(op))
                 case "recv":
                     operations.append(Recv.from_config(op))
+                case "close":
+                    operations.append(Close.from_config(op))
                 case "request":
                     operations.append(Request.from_config(op))
                 case "kill":
@@ -627,6 +728,9 @@ class TestCaseRunnerBase:
     def do_recv(self, recv: Recv) -> None:
         raise NotImplementedError()
 
+    def do_close(self, close: Close) -> None:
+        raise NotImplementedError()
+
     def do_request(self, req: Request) -> None:
         raise NotImplementedError()
 
@@ -678,6 +782,9 @@ class TestCaseRunnerBase:
                     case Recv():
                         assert isinstance(op, Recv)
                         self.do_recv(op)
+                    case Close():
+                        assert isinstance(op, Close)
+                        self.do_close(op)
                     case Request():
                         assert isinstance(op, Request)
                         self.do_request(op)
@@ -696,6 +803,10 @@ class StreamType(Enum):
     READABLE_PIPE = auto()
     WRITABLE_PIPE = auto()
     SOCKET = auto()
+    # A socket that was closed for writing; it can still be received on.
+    HALF_CLOSED_SOCKET = auto()
+    # A UDP socket; each send and recv is a single datagram.
+    DATAGRAM_SOCKET = auto()
 
 
 class TestCaseValidator(TestCaseRunnerBase):
@@ -731,14 +842,18 @@ class TestCaseValidator(TestCaseRunnerBase):
 
     def do_run(self, run: Run) -> None:
         self.assert_no_proc(run)
-        self.add_stream(run, "stdin", StreamType.WRITABLE_PIPE)
-        self.add_stream(run, "stdout", StreamType.READABLE_PIPE)
-        self.add_stream(run, "stderr", StreamType.READABLE_PIPE)
+        # Streams on the terminal aren't available to operations.
+        for name in STDIO_STREAMS:
+            if name not in run.tty:
+                typ = StreamType.WRITABLE_PIPE if name == "stdin" else StreamType.READABLE_PIPE
+                self.add_stream(run, name, typ)
         self._has_proc = True
 
     def do_write(self, write: Write) -> None:
         self.assert_proc(write)
         self.assert_stream(write, write.id, StreamType.WRITABLE_PIPE)
+        if write.close:
+            del self._streams[write.id]
 
     def do_read(self, read: Read) -> None:
         self.assert_proc(read)
@@ -751,17 +866,38 @@ class TestCaseValidator(TestCaseRunnerBase):
 
     def do_connect(self, conn: Connect) -> None:
         self.assert_proc(conn)
-        assert conn.protocol_type == ProtocolType.TCP, \
-            f"{self._config_path}: {conn}: {conn.protocol_type} not supported"
-        self.add_stream(conn, conn.id, StreamType.SOCKET)
+        match conn.protocol_type:
+            case ProtocolType.TCP:
+                self.add_stream(conn, conn.id, StreamType.SOCKET)
+            case ProtocolType.UDP:
+                self.add_stream(conn, conn.id, StreamType.DATAGRAM_SOCKET)
+            case _:
+                assert False, \
+                    f"{self._config_path}: {conn}: {conn.protocol_type} not supported"
 
     def do_send(self, send: Send) -> None:
         self.assert_proc(send)
-        self.assert_stream(send, send.id, StreamType.SOCKET)
+        if self._streams.get(send.id) != StreamType.DATAGRAM_SOCKET:
+            self.assert_stream(send, send.id, StreamType.SOCKET)
 
     def do_recv(self, recv: Recv) -> None:
         self.assert_proc(recv)
-        self.assert_stream(recv, recv.id, StreamType.SOCKET)
+        if self._streams.get(recv.id) not in (StreamType.HALF_CLOSED_SOCKET, StreamType.DATAGRAM_SOCKET):
+            self.assert_stream(recv, recv.id, StreamType.SOCKET)
+
+    def do_close(self, close: Close) -> None:
+        self.assert_proc(close)
+        if close.how == CloseMode.WRITE:
+            self.assert_stream(close, close.id, StreamType.SOCKET)
+            self._streams[close.id] = StreamType.HALF_CLOSED_SOCKET
+            return
+        # Datagram sockets have no connection to shut down or reset.
+        closable = [StreamType.HALF_CLOSED_SOCKET]
+        if close.how == CloseMode.FULL:
+            closable.append(StreamType.DATAGRAM_SOCKET)
+        if self._streams.get(close.id) not in closable:
+            self.assert_stream(close, close.id, StreamType.SOCKET)
+        del self._streams[close.id]
 
     def do_request(self, req: Request) -> None:
         self.assert_proc(req)
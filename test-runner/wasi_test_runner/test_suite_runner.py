@@ -5,6 +5,7 @@ import re
 import shutil
 import subprocess
 import socket
+import struct
 import threading
 import time
 
@@ -19,12 +20,18 @@ from .test_case import (
     Result, Failure, WasiVersion, Config, Outcome,
     TestCase, TestCaseRunnerBase, TestCaseValidator,
     Endpoint, EndpointMode, ServerKind, ECHO_HEADER_PREFIX, TRAILER_HEADER_PREFIX,
+    TCP_LISTEN_ENV_VAR, REFERENCE_TIME_ENV_VAR, TZ_ENV_VAR,
+    STDIO_STREAMS,
     # Operation types
-    Run, Read, Write, Wait, Send, Recv, Connect, Request, Kill
+    Run, Read, Write, Wait, Send, Recv, Close, CloseMode, Connect, Request, Kill,
+    ProtocolType
 )
 from .reporters import TestReporter
 from .test_suite import TestSuite, TestSuiteMeta
 
+# Enough for any datagram, so that `recv` never truncates one to fit.
+_MAX_DATAGRAM_SIZE = 65535
+
 
 class Manifest(NamedTuple):
     name: str
@@ -185,6 +192,9 @@ class TestCaseRunner(TestCaseRunnerBase):
     _endpoint_servers: List[_EndpointServer]
     _server_addrs: Dict[str, str]
     _windows_terminated_by_runner: bool
+    _tty: int | None
+    _tty_reader: threading.Thread | None
+    _tty_output: List[bytes]
 
     def __init__(self, config: Config, test_path: str, wasi_version: WasiVersion,
                  runtime: RuntimeAdapter) -> None:
@@ -201,6 +211,9 @@ class TestCaseRunner(TestCaseRunnerBase):
         self._endpoint_servers = []
         self._server_addrs = {}
         self._windows_terminated_by_runner = False
+        self._tty = None
+        self._tty_reader = None
+        self._tty_output = []
 
     def _start_servers(self) -> None:
         if self._server_addrs:
@@ -221,11 +234,42 @@ class TestCaseRunner(TestCaseRunnerBase):
         _cleanup_test_output(d)
         self._cleanup_dirs.append(d)
 
+    def _open_tty(self) -> int:
+        # POSIX only, like the terminals it provides.
+        import pty  # pylint: disable=import-outside-toplevel
+
+        master, slave = pty.openpty()
+        self._tty = master
+        self._tty_output = []
+        # The guest blocks once the terminal's buffer is full, so keep it
+        # drained for as long as the guest runs.
+        self._tty_reader = threading.Thread(
+            target=_drain_tty, args=(master, self._tty_output), daemon=True)
+        self._tty_reader.start()
+        return slave
+
+    def _close_tty(self) -> str | None:
+        if self._tty is None:
+            return None
+        assert self._tty_reader is not None
+        # The reader stops once the last process holding the terminal exits.
+        self._tty_reader.join(timeout=5)
+        os.close(self._tty)
+        self._tty = None
+        self._tty_reader = None
+        return b"".join(self._tty_output).decode("utf-8", errors="replace")
+
     def _wait(self, timeout: float | None) -> Tuple[int, str, str]:
         proc = self._proc
         assert proc is not None
         out, err = proc.communicate(timeout=timeout)
         self._proc = None
+        # Whatever went to the terminal stands in for the streams on it.
+        tty = self._close_tty()
+        if out is None:
+            out = tty
+        elif err is None:
+            err = tty
         return proc.returncode, out, err
 
     def fail_unexpected(self, msg: str) -> None:
@@ -267,17 +311,25 @@ class TestCaseRunner(TestCaseRunnerBase):
         return self._http_server
 
     def do_run(self, run: Run) -> None:
-        if run.root:
-            self._add_cleanup_dir(run.root)
+        for host_dir, _ in run.preopens:
+            self._add_cleanup_dir(host_dir)
         self._start_servers()
         proposals = self.config.proposals_as_str()
         wasi_env = dict(run.env)
         wasi_env.update(self._server_addrs)
-        argv = self._runtime.compute_argv(
-            self._test_path, run.args, wasi_env, run.root, proposals,
-            self.config.world, self._wasi_version)
-        self._last_argv = argv
+        if run.tcplisten:
+            # The runtime binds this address; the guest echoes it back on
+            # stdout for `connect`, like a guest that listens by itself.
+            wasi_env[TCP_LISTEN_ENV_VAR] = _reserve_closed_addr()
+        if run.reference_time:
+            # Taken just before spawning, so the guest's clock can only be
+            # ahead of it by however long the runtime takes to start.
+            wasi_env[REFERENCE_TIME_ENV_VAR] = str(time.time_ns())
         try:
+            argv = self._runtime.compute_argv(
+                self._test_path, run.args, wasi_env, run.preopens, proposals,
+                self.config.world, self._wasi_version)
+            self._last_argv = argv
             creationflags = 0
             if os.name == "nt":
                 creationflags = getattr(subprocess, "CREATE_NEW_PROCESS_GROUP")
@@ -285,26 +337,38 @@ class TestCaseRunner(TestCaseRunnerBase):
             env = os.environ.copy()
             if self.config.debug:
                 env["DEBUG"] = "true"
+            # The host's timezone is what the guest sees through
+            # `wasi:clocks/timezone`, so a guest `TZ` applies to the runtime too.
+            if TZ_ENV_VAR in run.env:
+                env[TZ_ENV_VAR] = run.env[TZ_ENV_VAR]
+
+            stdio: List[Any] = [subprocess.PIPE] * len(STDIO_STREAMS)
+            if run.tty:
+                slave = self._open_tty()
+                stdio = [slave if name in run.tty else subprocess.PIPE
+                         for name in STDIO_STREAMS]
 
             # pylint: disable-msg=consider-using-with
-            self._proc = subprocess.Popen(
-                argv,
-                stdin=subprocess.PIPE,
-                stdout=subprocess.PIPE,
-                stderr=subprocess.PIPE,
-                env=env,
-                text=True,
-                creationflags=creationflags,
-            )
-            stdin, stdout, stderr = \
-                self._proc.stdin, self._proc.stdout, self._proc.stderr
-            assert stdin is not None
-            assert stdout is not None
-            assert stderr is not None
-            self.add_pipe('stdin', stdin)
-            self.add_pipe('stdout', stdout)
-            self.add_pipe('stderr', stderr)
-        except (OSError, ValueError) as e:
+            try:
+                self._proc = subprocess.Popen(
+                    argv,
+                    stdin=stdio[0],
+                    stdout=stdio[1],
+                    stderr=stdio[2],
+                    env=env,
+                    text=True,
+                    creationflags=creationflags,
+                )
+            finally:
+                if run.tty:
+                    # Only the guest should hold the terminal open.
+                    os.close(slave)
+            pipes = (self._proc.stdin, self._proc.stdout, self._proc.stderr)
+            for name, pipe in zip(STDIO_STREAMS, pipes):
+                assert (pipe is None) == (name in run.tty)
+                if pipe is not None:
+                    self.add_pipe(name, pipe)
+        except (OSError, ValueError, ImportError) as e:
             self.fail_unexpected(f"Failed to start process: {e}")
 
     def do_read(self, read: Read) -> None:
@@ -316,8 +380,15 @@ class TestCaseRunner(TestCaseRunnerBase):
 
     def do_write(self, write: Write) -> None:
         stream = self.get_pipe(write.id)
-        stream.write(write.payload)
+        for _ in range(write.repeat):
+            stream.write(write.payload)
         stream.flush()
+        if write.close:
+            stream.close()
+            del self._pipes[write.id]
+            if self._proc is not None and stream is self._proc.stdin:
+                # Otherwise `communicate` would try to flush it.
+                self._proc.stdin = None
 
     def do_wait(self, wait: Wait) -> None:
         try:
@@ -343,7 +414,12 @@ class TestCaseRunner(TestCaseRunnerBase):
         match line.split(':'):
             case [host, port_str] if port_str.isnumeric():
                 port = int(port_str)
-                sock = socket.socket(socket.AF_INET, socket.SOCK_STREAM)
+                if conn.protocol_type == ProtocolType.UDP:
+                    sock = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+                    # Lost datagrams would otherwise leave `recv` waiting forever.
+                    sock.settimeout(5)
+                else:
+                    sock = socket.socket(socket.AF_INET, socket.SOCK_STREAM)
                 try:
                     sock.connect((host, port))
                     self.add_socket(conn.id, sock)
@@ -360,14 +436,20 @@ class TestCaseRunner(TestCaseRunnerBase):
     def do_send(self, send: Send) -> None:
         sock = self.get_socket(send.id)
         try:
-            sock.sendall(send.payload.encode('utf-8'))
+            if sock.type == socket.SOCK_DGRAM:
+                sock.send(send.payload.encode('utf-8'))
+            else:
+                sock.sendall(send.payload.encode('utf-8'))
         except (OSError, socket.error) as e:
             self.fail_unexpected(f"{send}: Failed to send data: {e}")
 
     def do_recv(self, recv: Recv) -> None:
         sock = self.get_socket(recv.id)
         try:
-            response_bytes = sock.recv(len(recv.payload))
+            if sock.type == socket.SOCK_DGRAM:
+                response_bytes = sock.recv(_MAX_DATAGRAM_SIZE)
+            else:
+                response_bytes = sock.recv(len(recv.payload))
             response = response_bytes.decode('utf-8')
             if response != recv.payload:
                 self.fail_unexpected(f"{recv}: Expected {recv.payload}, got {response}")
@@ -376,6 +458,22 @@ class TestCaseRunner(TestCaseRunnerBase):
         except UnicodeDecodeError as e:
             self.fail_unexpected(f"{recv}: Failed to decode response: {e}")
 
+    def do_close(self, close: Close) -> None:
+        sock = self.get_socket(close.id)
+        try:
+            match close.how:
+                case CloseMode.WRITE:
+                    sock.shutdown(socket.SHUT_WR)
+                    return
+                case CloseMode.RESET:
+                    # Lingering for no time at all makes `close` send a reset
+                    # rather than a FIN.
+                    sock.setsockopt(socket.SOL_SOCKET, socket.SO_LINGER, struct.pack("ii", 1, 0))
+            sock.close()
+            del self._sockets[close.id]
+        except OSError as e:
+            self.fail_unexpected(f"{close}: Failed to close connection: {e}")
+
     def do_request(self, req: Request) -> None:
         # pylint: disable-msg=too-many-return-statements
         # Only HTTP tests need requests; keep CLI tests runnable without it.
@@ -454,6 +552,7 @@ class TestCaseRunner(TestCaseRunnerBase):
         for pipe in self._pipes.values():
             pipe.close()
         self._pipes = {}
+        self._close_tty()
 
         for d in self._cleanup_dirs:
             _cleanup_test_output(d)
@@ -521,6 +620,18 @@ def _skip_single_test(spec: _TestSpec) -> TestCase:
     )
 
 
+def _drain_tty(fd: int, output: List[bytes]) -> None:
+    while True:
+        try:
+            data = os.read(fd, 4096)
+        except OSError:
+            # Linux reports EIO once no process has the terminal open.
+            return
+        if not data:
+            return
+        output.append(data)
+
+
 def _append_stdout_and_stderr(msg: str, out: str | None, err: str | None) -> str:
     if out:
         msg += f"\n\n==STDOUT==\n{out}"
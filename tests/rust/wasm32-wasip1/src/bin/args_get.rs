@@ -0,0 +1,67 @@
+use wasip1 as wasi;
+
+// Must match the `args` in args_get.json.
+fn expected_args() -> Vec<String> {
+    let mut args: Vec<String> = [
+        "",
+        "héllo wörld",
+        "日本語",
+        "🦀",
+        "key=value=more",
+        "  spaced  ",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    args.extend((0..300).map(|i| format!("arg-{:03}", i)));
+    args.push("x".repeat(8192));
+    args.push("é".repeat(2048));
+    args
+}
+
+unsafe fn test_args_get() {
+    let (argc, buf_size) = wasi::args_sizes_get().expect("args_sizes_get");
+
+    let mut argv: Vec<*mut u8> = Vec::with_capacity(argc);
+    let mut buf: Vec<u8> = Vec::with_capacity(buf_size);
+    wasi::args_get(argv.as_mut_ptr(), buf.as_mut_ptr()).expect("args_get");
+    argv.set_len(argc);
+    buf.set_len(buf_size);
+
+    // Every argument is a NUL-terminated string inside the buffer, and the
+    // buffer holds nothing else.
+    let mut args: Vec<String> = Vec::with_capacity(argc);
+    let mut total = 0;
+    for ptr in argv {
+        let offset = ptr.offset_from(buf.as_ptr()) as usize;
+        assert!(offset < buf_size, "argument pointer outside of argv_buf");
+        let len = buf[offset..]
+            .iter()
+            .position(|b| *b == 0)
+            .expect("argument is not NUL-terminated");
+        let arg = String::from_utf8(buf[offset..offset + len].to_vec())
+            .expect("argument is not valid UTF-8");
+        total += len + 1;
+        args.push(arg);
+    }
+    assert_eq!(total, buf_size, "args_sizes_get reported the wrong size");
+
+    // The first argument is the program name, whatever the runtime made it.
+    let expected = expected_args();
+    assert_eq!(args.len(), expected.len() + 1, "argument count mismatch");
+    for (i, (arg, expected)) in args[1..].iter().zip(expected.iter()).enumerate() {
+        assert_eq!(
+            arg,
+            expected,
+            "argument {} differs ({} bytes, expected {} bytes)",
+            i + 1,
+            arg.len(),
+            expected.len()
+        );
+    }
+}
+
+fn main() {
+    // Run the tests.
+    unsafe { test_args_get() }
+}
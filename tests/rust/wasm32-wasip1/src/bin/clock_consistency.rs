@@ -0,0 +1,119 @@
+use std::{env, process};
+use wasi_tests::assert_errno;
+use wasip1 as wasi;
+
+const SAMPLES: usize = 10_000;
+// The guest's clock may run a little behind the runner's, and ahead of it by
+// however long the runtime takes to start, which can be long in CI.
+const MAX_BEHIND: u64 = 5_000_000_000; // 5 seconds
+const MAX_AHEAD: u64 = 300_000_000_000; // 5 minutes
+
+const OPTIONAL_CLOCKS: [wasi::Clockid; 2] = [
+    wasi::CLOCKID_PROCESS_CPUTIME_ID,
+    wasi::CLOCKID_THREAD_CPUTIME_ID,
+];
+
+unsafe fn test_required_clock(id: wasi::Clockid) {
+    let res = wasi::clock_res_get(id).expect("clock_res_get on a required clock");
+    assert!(res > 0, "{:?} resolution should be positive", id);
+    assert!(
+        res <= 1_000_000_000,
+        "{:?} resolution should be at most a second; got {}ns",
+        id,
+        res
+    );
+
+    // Any precision is accepted, whether or not the host can honor it.
+    for precision in [0, 1, 1_000, res, u64::MAX] {
+        wasi::clock_time_get(id, precision)
+            .unwrap_or_else(|_| panic!("{:?} with precision {}", id, precision));
+    }
+}
+
+unsafe fn test_optional_clock(id: wasi::Clockid) {
+    let res = match wasi::clock_res_get(id) {
+        Ok(res) => res,
+        Err(err) => {
+            assert_errno!(err, wasi::ERRNO_INVAL, wasi::ERRNO_NOTSUP);
+            // A clock without a resolution can't be read either.
+            assert_errno!(
+                wasi::clock_time_get(id, 0).expect_err("clock_time_get on an unsupported clock"),
+                wasi::ERRNO_INVAL,
+                wasi::ERRNO_NOTSUP
+            );
+            return;
+        }
+    };
+    assert!(res > 0, "{:?} resolution should be positive", id);
+
+    // CPU time only moves forward.
+    let mut prev = wasi::clock_time_get(id, 0).expect("clock_time_get on a supported clock");
+    for _ in 0..SAMPLES / 10 {
+        let now = wasi::clock_time_get(id, 0).expect("clock_time_get on a supported clock");
+        assert!(
+            now >= prev,
+            "{:?} went backwards from {} to {}",
+            id,
+            prev,
+            now
+        );
+        prev = now;
+    }
+}
+
+unsafe fn test_monotonic_samples() {
+    let first = wasi::clock_time_get(wasi::CLOCKID_MONOTONIC, 1).expect("clock_time_get");
+    let mut prev = first;
+    for _ in 0..SAMPLES {
+        let now = wasi::clock_time_get(wasi::CLOCKID_MONOTONIC, 1).expect("clock_time_get");
+        assert!(
+            now >= prev,
+            "CLOCK_MONOTONIC went backwards from {} to {}",
+            prev,
+            now
+        );
+        prev = now;
+    }
+    assert!(prev >= first, "CLOCK_MONOTONIC should be monotonic");
+}
+
+unsafe fn test_realtime_reference(reference: wasi::Timestamp) {
+    let now = wasi::clock_time_get(wasi::CLOCKID_REALTIME, 1).expect("clock_time_get");
+    assert!(
+        now + MAX_BEHIND >= reference,
+        "CLOCK_REALTIME {} is more than {}ns behind the runner's {}",
+        now,
+        MAX_BEHIND,
+        reference
+    );
+    assert!(
+        now <= reference + MAX_AHEAD,
+        "CLOCK_REALTIME {} is more than {}ns ahead of the runner's {}",
+        now,
+        MAX_AHEAD,
+        reference
+    );
+}
+
+unsafe fn test_clock_consistency(reference: wasi::Timestamp) {
+    test_required_clock(wasi::CLOCKID_REALTIME);
+    test_required_clock(wasi::CLOCKID_MONOTONIC);
+    for id in OPTIONAL_CLOCKS {
+        test_optional_clock(id);
+    }
+    test_monotonic_samples();
+    test_realtime_reference(reference);
+}
+
+fn main() {
+    let reference = match env::var("REFERENCE_TIME_NS").map(|ns| ns.parse()) {
+        Ok(Ok(reference)) => reference,
+        _ => {
+            eprintln!("REFERENCE_TIME_NS should be set to the host time in nanoseconds");
+            process::exit(1)
+        }
+    };
+
+    // Run the tests.
+    unsafe { test_clock_consistency(reference) }
+}
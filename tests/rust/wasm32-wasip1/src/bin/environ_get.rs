@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use wasip1 as wasi;
+
+// Must match the `env` in environ_get.json.
+fn expected_environ() -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = [
+        ("EMPTY", ""),
+        ("UTF8", "héllo wörld 日本語 🦀"),
+        ("EQUALS", "a=b==c="),
+        ("SPACED", "  spaced  "),
+    ]
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    env.extend((0..300).map(|i| (format!("VAR_{:03}", i), format!("value-{:03}", i))));
+    env.push(("LONG".to_string(), "x".repeat(8192)));
+    env.push(("LONG_UTF8".to_string(), "é".repeat(2048)));
+    env
+}
+
+unsafe fn test_environ_get() {
+    let (count, buf_size) = wasi::environ_sizes_get().expect("environ_sizes_get");
+
+    let mut environ: Vec<*mut u8> = Vec::with_capacity(count);
+    let mut buf: Vec<u8> = Vec::with_capacity(buf_size);
+    wasi::environ_get(environ.as_mut_ptr(), buf.as_mut_ptr()).expect("environ_get");
+    environ.set_len(count);
+    buf.set_len(buf_size);
+
+    // Every variable is a NUL-terminated `key=value` string inside the
+    // buffer, and the buffer holds nothing else.
+    let mut vars: HashMap<String, String> = HashMap::new();
+    let mut total = 0;
+    for ptr in environ {
+        let offset = ptr.offset_from(buf.as_ptr()) as usize;
+        assert!(
+            offset < buf_size,
+            "environment pointer outside of environ_buf"
+        );
+        let len = buf[offset..]
+            .iter()
+            .position(|b| *b == 0)
+            .expect("environment variable is not NUL-terminated");
+        let var = String::from_utf8(buf[offset..offset + len].to_vec())
+            .expect("environment variable is not valid UTF-8");
+        total += len + 1;
+        // Only the first `=` separates the key; the rest belong to the value.
+        let (key, value) = var
+            .split_once('=')
+            .expect("environment variable has no `=`");
+        let prev = vars.insert(key.to_string(), value.to_string());
+        assert!(prev.is_none(), "duplicate environment variable {}", key);
+    }
+    assert_eq!(total, buf_size, "environ_sizes_get reported the wrong size");
+
+    // The runner may add variables of its own, so only check that ours are
+    // all there, untruncated.
+    for (key, expected) in expected_environ() {
+        let value = vars
+            .get(&key)
+            .unwrap_or_else(|| panic!("missing environment variable {}", key));
+        assert_eq!(
+            value,
+            &expected,
+            "environment variable {} differs ({} bytes, expected {} bytes)",
+            key,
+            value.len(),
+            expected.len()
+        );
+    }
+}
+
+fn main() {
+    // Run the tests.
+    unsafe { test_environ_get() }
+}
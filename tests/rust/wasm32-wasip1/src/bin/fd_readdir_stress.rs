@@ -0,0 +1,301 @@
+use std::collections::{HashMap, HashSet};
+use std::{mem, process, slice, str};
+use wasi_tests::{create_file, create_tmp_dir, root_directory};
+use wasip1 as wasi;
+
+// Small enough that most calls return a single entry followed by a truncated
+// one, and too small for any of the long names.
+const TINY_BUF_LEN: usize = 64;
+const BUF_LEN: usize = 4096;
+const N: usize = 2000;
+// Files created while a listing is in progress, a few at a time, so that the
+// listing still comes to an end if they all show up.
+const ADDED: usize = 200;
+
+struct DirEntry {
+    dirent: wasi::Dirent,
+    name: String,
+}
+
+// Manually reading the output from fd_readdir is tedious and repetitive,
+// so encapsulate it into an iterator
+struct ReadDir<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> ReadDir<'a> {
+    fn from_slice(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+}
+
+impl<'a> Iterator for ReadDir<'a> {
+    type Item = DirEntry;
+
+    fn next(&mut self) -> Option<DirEntry> {
+        unsafe {
+            if self.buf.len() < mem::size_of::<wasi::Dirent>() {
+                return None;
+            }
+
+            // Read the data
+            let dirent_ptr = self.buf.as_ptr() as *const wasi::Dirent;
+            let dirent = dirent_ptr.read_unaligned();
+
+            if self.buf.len() < mem::size_of::<wasi::Dirent>() + dirent.d_namlen as usize {
+                return None;
+            }
+
+            let name_ptr = dirent_ptr.offset(1) as *const u8;
+            let namelen = dirent.d_namlen as usize;
+            let slice = slice::from_raw_parts(name_ptr, namelen);
+            let name = str::from_utf8(slice).expect("invalid utf8").to_owned();
+
+            // Update the internal state
+            let delta = mem::size_of_val(&dirent) + namelen;
+            self.buf = &self.buf[delta..];
+
+            DirEntry { dirent, name }.into()
+        }
+    }
+}
+
+/// Return the complete entries starting at `cookie` plus a bool indicating
+/// EOF. Truncated entries are dropped, as a libc would, and the buffer only
+/// grows when not even one entry fits in it.
+unsafe fn exec_fd_readdir(
+    fd: wasi::Fd,
+    cookie: wasi::Dircookie,
+    buf_len: usize,
+) -> (Vec<DirEntry>, bool) {
+    let mut buf = vec![0; buf_len];
+    loop {
+        let bufused =
+            wasi::fd_readdir(fd, buf.as_mut_ptr(), buf.len(), cookie).expect("failed fd_readdir");
+        assert!(bufused <= buf.len());
+
+        let dirs: Vec<_> = ReadDir::from_slice(&buf[..bufused]).collect();
+        let eof = bufused < buf.len();
+        if !dirs.is_empty() || eof {
+            return (dirs, eof);
+        }
+        let len = buf.len() * 2;
+        buf.resize(len, 0);
+    }
+}
+
+unsafe fn read_all(fd: wasi::Fd, buf_len: usize) -> Vec<DirEntry> {
+    let mut entries = Vec::new();
+    let mut cookie = 0;
+    loop {
+        let (dirs, eof) = exec_fd_readdir(fd, cookie, buf_len);
+        if let Some(last) = dirs.last() {
+            cookie = last.dirent.d_next;
+        }
+        entries.extend(dirs);
+        if eof {
+            return entries;
+        }
+    }
+}
+
+fn file_name(i: usize) -> String {
+    if i % 7 == 0 {
+        format!("{}.{:04}", "x".repeat(128), i)
+    } else {
+        format!("file.{:04}", i)
+    }
+}
+
+fn count_names(entries: &[DirEntry]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for entry in entries {
+        assert_eq!(
+            entry.dirent.d_namlen as usize,
+            entry.name.len(),
+            "d_namlen doesn't match the name"
+        );
+        *counts.entry(entry.name.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn assert_listing(entries: &[DirEntry], expected: &[String]) {
+    let counts = count_names(entries);
+    for (name, count) in &counts {
+        assert_eq!(*count, 1, "entry {} was returned {} times", name, count);
+    }
+    for name in [".", ".."]
+        .iter()
+        .map(|s| s.to_string())
+        .chain(expected.iter().cloned())
+    {
+        assert!(counts.contains_key(&name), "entry {} is missing", name);
+    }
+    assert_eq!(
+        entries.len(),
+        expected.len() + 2,
+        "expected {} entries plus . and ..",
+        expected.len()
+    );
+}
+
+unsafe fn test_tiny_buffer(dir_fd: wasi::Fd, names: &[String]) {
+    let entries = read_all(dir_fd, TINY_BUF_LEN);
+    assert_listing(&entries, names);
+    for entry in &entries {
+        if entry.name != "." && entry.name != ".." {
+            assert_eq!(
+                entry.dirent.d_type,
+                wasi::FILETYPE_REGULAR_FILE,
+                "type for {}",
+                entry.name
+            );
+        }
+    }
+
+    // A roomy buffer sees exactly the same entries.
+    let roomy = read_all(dir_fd, BUF_LEN);
+    assert_listing(&roomy, names);
+}
+
+unsafe fn test_resume_from_cookies(dir_fd: wasi::Fd) {
+    let entries = read_all(dir_fd, TINY_BUF_LEN);
+
+    // Without changes to the directory, cookies are stable across listings.
+    let again = read_all(dir_fd, BUF_LEN);
+    assert_eq!(entries.len(), again.len(), "listings differ in length");
+    for (a, b) in entries.iter().zip(again.iter()) {
+        assert_eq!(a.name, b.name, "listings differ in order");
+        assert_eq!(a.dirent.d_next, b.dirent.d_next, "cookie for {}", a.name);
+    }
+
+    // Resuming from any entry's cookie continues right after that entry.
+    let (dirs, _) = exec_fd_readdir(dir_fd, 0, TINY_BUF_LEN);
+    assert_eq!(dirs[0].name, entries[0].name, "cookie 0 starts the listing");
+    for (i, entry) in entries.iter().enumerate() {
+        let (dirs, eof) = exec_fd_readdir(dir_fd, entry.dirent.d_next, TINY_BUF_LEN);
+        match entries.get(i + 1) {
+            Some(next) => assert_eq!(
+                dirs.first().map(|d| d.name.as_str()),
+                Some(next.name.as_str()),
+                "resuming after {}",
+                entry.name
+            ),
+            None => {
+                assert!(eof, "resuming after the last entry should hit EOF");
+                assert!(dirs.is_empty(), "no entries after the last one");
+            }
+        }
+    }
+}
+
+unsafe fn test_modify_between_calls(dir_fd: wasi::Fd, names: &[String]) -> Vec<String> {
+    // Files the listing hasn't reached yet are removed, and new ones created,
+    // while it is in progress. Either may or may not be seen, but nothing is
+    // seen twice and every file left alone is seen. WASI doesn't say what
+    // becomes of cookies when entries before them go away, so only unseen
+    // files are removed.
+    let mut candidates: Vec<String> = names
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 10 == 5)
+        .map(|(_, name)| name.clone())
+        .collect();
+    candidates.reverse();
+    let mut removed: HashSet<String> = HashSet::new();
+    let mut added: Vec<String> = Vec::new();
+
+    let mut entries = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cookie = 0;
+    for call in 0.. {
+        let (dirs, eof) = exec_fd_readdir(dir_fd, cookie, TINY_BUF_LEN);
+        if let Some(last) = dirs.last() {
+            cookie = last.dirent.d_next;
+        }
+        seen.extend(dirs.iter().map(|d| d.name.clone()));
+        entries.extend(dirs);
+        if eof {
+            break;
+        }
+
+        while let Some(name) = candidates.pop() {
+            if !seen.contains(&name) {
+                wasi::path_unlink_file(dir_fd, &name).expect("removing a file");
+                removed.insert(name);
+                break;
+            }
+        }
+        if added.len() < ADDED && call % 10 == 0 {
+            let name = format!("added.{:04}", added.len());
+            create_file(dir_fd, &name);
+            added.push(name);
+        }
+    }
+
+    let counts = count_names(&entries);
+    for (name, count) in &counts {
+        assert_eq!(*count, 1, "entry {} was returned {} times", name, count);
+        assert!(
+            name == "." || name == ".." || names.contains(name) || added.contains(name),
+            "unexpected entry {}",
+            name
+        );
+    }
+    for name in [".", ".."].iter().map(|s| s.to_string()).chain(
+        names
+            .iter()
+            .filter(|name| !removed.contains(*name))
+            .cloned(),
+    ) {
+        assert!(counts.contains_key(&name), "entry {} is missing", name);
+    }
+
+    // Whatever was not removed is still there for the caller to clean up.
+    names
+        .iter()
+        .filter(|name| !removed.contains(*name))
+        .cloned()
+        .chain(added)
+        .collect()
+}
+
+unsafe fn test_fd_readdir_stress(dir_fd: wasi::Fd) {
+    let names: Vec<String> = (0..N).map(file_name).collect();
+    for name in &names {
+        create_file(dir_fd, name);
+    }
+
+    test_tiny_buffer(dir_fd, &names);
+    test_resume_from_cookies(dir_fd);
+    let remaining = test_modify_between_calls(dir_fd, &names);
+
+    for name in remaining {
+        wasi::path_unlink_file(dir_fd, &name).expect("removing a file");
+    }
+}
+
+fn main() {
+    let base_dir_fd = match root_directory() {
+        Ok(dir_fd) => dir_fd,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+
+    const DIR_NAME: &str = "fd_readdir_stress_dir.cleanup";
+    let dir_fd;
+    unsafe {
+        dir_fd = create_tmp_dir(base_dir_fd, DIR_NAME);
+    }
+
+    // Run the tests.
+    unsafe { test_fd_readdir_stress(dir_fd) }
+
+    unsafe {
+        wasi::fd_close(dir_fd).unwrap();
+    }
+    unsafe { wasi::path_remove_directory(base_dir_fd, DIR_NAME).expect("failed to remove dir") }
+}
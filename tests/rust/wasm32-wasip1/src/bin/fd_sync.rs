@@ -0,0 +1,136 @@
+use std::process;
+use wasi_tests::{assert_errno, create_tmp_dir, drop_rights, root_directory, supports_rights};
+use wasip1 as wasi;
+
+const CONTENTS: &[u8] = b"durable data";
+
+unsafe fn test_file_sync(dir_fd: wasi::Fd) {
+    let file_fd = wasi::path_open(
+        dir_fd,
+        0,
+        "file",
+        wasi::OFLAGS_CREAT,
+        wasi::RIGHTS_FD_READ
+            | wasi::RIGHTS_FD_WRITE
+            | wasi::RIGHTS_FD_SYNC
+            | wasi::RIGHTS_FD_DATASYNC,
+        0,
+        0,
+    )
+    .expect("opening a file");
+    assert!(
+        file_fd > libc::STDERR_FILENO as wasi::Fd,
+        "file descriptor range check",
+    );
+
+    // Syncing works both on a fresh file and after writing to it.
+    wasi::fd_sync(file_fd).expect("fd_sync on an empty file");
+    wasi::fd_datasync(file_fd).expect("fd_datasync on an empty file");
+
+    let ciovec = wasi::Ciovec {
+        buf: CONTENTS.as_ptr(),
+        buf_len: CONTENTS.len(),
+    };
+    let nwritten = wasi::fd_write(file_fd, &[ciovec]).expect("writing to a file");
+    assert_eq!(nwritten, CONTENTS.len(), "nwritten bytes check");
+
+    wasi::fd_datasync(file_fd).expect("fd_datasync after writing");
+    wasi::fd_sync(file_fd).expect("fd_sync after writing");
+
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_eq!(stat.size, CONTENTS.len() as u64, "file size after syncing");
+
+    let mut buf = [0; 32];
+    let iovec = wasi::Iovec {
+        buf: buf.as_mut_ptr(),
+        buf_len: buf.len(),
+    };
+    let nread = wasi::fd_pread(file_fd, &[iovec], 0).expect("reading the file back");
+    assert_eq!(&buf[..nread], CONTENTS, "file contents after syncing");
+
+    if !supports_rights(file_fd) {
+        eprintln!("implementation doesn't support rights, skipping");
+    } else {
+        // Each call is gated by its own right.
+        drop_rights(file_fd, wasi::RIGHTS_FD_SYNC, 0);
+        assert_errno!(
+            wasi::fd_sync(file_fd).expect_err("fd_sync without RIGHTS_FD_SYNC"),
+            wasi::ERRNO_NOTCAPABLE
+        );
+        wasi::fd_datasync(file_fd).expect("fd_datasync without RIGHTS_FD_SYNC");
+
+        drop_rights(file_fd, wasi::RIGHTS_FD_DATASYNC, 0);
+        assert_errno!(
+            wasi::fd_datasync(file_fd).expect_err("fd_datasync without RIGHTS_FD_DATASYNC"),
+            wasi::ERRNO_NOTCAPABLE
+        );
+    }
+
+    wasi::fd_close(file_fd).expect("closing a file");
+    wasi::path_unlink_file(dir_fd, "file").expect("removing a file");
+}
+
+unsafe fn test_dir_sync(dir_fd: wasi::Fd) {
+    wasi::path_create_directory(dir_fd, "subdir").expect("creating a directory");
+    let subdir_fd = wasi::path_open(
+        dir_fd,
+        0,
+        "subdir",
+        wasi::OFLAGS_DIRECTORY,
+        wasi::RIGHTS_FD_SYNC | wasi::RIGHTS_FD_DATASYNC,
+        0,
+        0,
+    )
+    .expect("opening a directory");
+
+    wasi::fd_sync(subdir_fd).expect("fd_sync on a directory");
+    wasi::fd_datasync(subdir_fd).expect("fd_datasync on a directory");
+
+    if supports_rights(subdir_fd) {
+        drop_rights(
+            subdir_fd,
+            wasi::RIGHTS_FD_SYNC | wasi::RIGHTS_FD_DATASYNC,
+            0,
+        );
+        assert_errno!(
+            wasi::fd_sync(subdir_fd).expect_err("fd_sync on a directory without rights"),
+            wasi::ERRNO_NOTCAPABLE
+        );
+        assert_errno!(
+            wasi::fd_datasync(subdir_fd).expect_err("fd_datasync on a directory without rights"),
+            wasi::ERRNO_NOTCAPABLE
+        );
+    }
+
+    wasi::fd_close(subdir_fd).expect("closing a directory");
+    wasi::path_remove_directory(dir_fd, "subdir").expect("removing a directory");
+}
+
+unsafe fn test_fd_sync(dir_fd: wasi::Fd) {
+    test_file_sync(dir_fd);
+    test_dir_sync(dir_fd);
+}
+
+fn main() {
+    let base_dir_fd = match root_directory() {
+        Ok(dir_fd) => dir_fd,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+
+    const DIR_NAME: &str = "fd_sync_dir.cleanup";
+    let dir_fd;
+    unsafe {
+        dir_fd = create_tmp_dir(base_dir_fd, DIR_NAME);
+    }
+
+    // Run the tests.
+    unsafe { test_fd_sync(dir_fd) }
+
+    unsafe {
+        wasi::fd_close(dir_fd).unwrap();
+    }
+    unsafe { wasi::path_remove_directory(base_dir_fd, DIR_NAME).expect("failed to remove dir") }
+}
@@ -0,0 +1,209 @@
+use std::process;
+use wasi_tests::{TESTCONFIG, create_tmp_dir, root_directory};
+use wasip1 as wasi;
+
+// Arbitrary timestamps with every nanosecond digit significant.
+const ATIM: wasi::Timestamp = 1_600_000_000_123_456_789;
+const MTIM: wasi::Timestamp = 1_500_000_000_987_654_321;
+const LINK_ATIM: wasi::Timestamp = 1_400_000_000_111_111_111;
+const LINK_MTIM: wasi::Timestamp = 1_300_000_000_222_222_222;
+// The host takes the time for the `_NOW` flags from a clock of its own,
+// which may be a coarser one that lags `clock_time_get` by a tick or so.
+const NOW_SLACK_NS: wasi::Timestamp = 50_000_000;
+
+// Filesystems store timestamps with varying precision, so a value read back
+// only has to be within the configured granularity of the one written.
+fn assert_time(actual: wasi::Timestamp, expected: wasi::Timestamp, what: &str) {
+    let granularity = TESTCONFIG.timestamp_granularity();
+    assert!(
+        actual.abs_diff(expected) < granularity,
+        "{} should be {} (within {}ns); got {}",
+        what,
+        expected,
+        granularity,
+        actual
+    );
+}
+
+fn assert_time_between(
+    actual: wasi::Timestamp,
+    before: wasi::Timestamp,
+    after: wasi::Timestamp,
+    what: &str,
+) {
+    let slack = TESTCONFIG.timestamp_granularity() + NOW_SLACK_NS;
+    assert!(
+        actual + slack > before && actual < after + slack,
+        "{} should be between {} and {}; got {}",
+        what,
+        before,
+        after,
+        actual
+    );
+}
+
+unsafe fn now() -> wasi::Timestamp {
+    wasi::clock_time_get(wasi::CLOCKID_REALTIME, 1).expect("clock_time_get")
+}
+
+unsafe fn test_fd_filestat_set_times(dir_fd: wasi::Fd, file_fd: wasi::Fd) {
+    // Explicit values round-trip to the nanosecond, or to the granularity.
+    wasi::fd_filestat_set_times(
+        file_fd,
+        ATIM,
+        MTIM,
+        wasi::FSTFLAGS_ATIM | wasi::FSTFLAGS_MTIM,
+    )
+    .expect("fd_filestat_set_times");
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time(stat.atim, ATIM, "atim");
+    assert_time(stat.mtim, MTIM, "mtim");
+
+    // Setting only one of them leaves the other alone.
+    wasi::fd_filestat_set_times(file_fd, 0, MTIM + 1_000, wasi::FSTFLAGS_MTIM)
+        .expect("fd_filestat_set_times with only FSTFLAGS_MTIM");
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time(stat.atim, ATIM, "atim after setting mtim");
+    assert_time(stat.mtim, MTIM + 1_000, "mtim");
+
+    // The `_NOW` flags use the current time and ignore the values passed in.
+    let before = now();
+    wasi::fd_filestat_set_times(
+        file_fd,
+        ATIM,
+        MTIM,
+        wasi::FSTFLAGS_ATIM_NOW | wasi::FSTFLAGS_MTIM_NOW,
+    )
+    .expect("fd_filestat_set_times with FSTFLAGS_*_NOW");
+    let after = now();
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time_between(stat.atim, before, after, "atim set to now");
+    assert_time_between(stat.mtim, before, after, "mtim set to now");
+}
+
+unsafe fn test_path_filestat_set_times(dir_fd: wasi::Fd) {
+    wasi::path_filestat_set_times(
+        dir_fd,
+        0,
+        "file",
+        ATIM,
+        MTIM,
+        wasi::FSTFLAGS_ATIM | wasi::FSTFLAGS_MTIM,
+    )
+    .expect("path_filestat_set_times");
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time(stat.atim, ATIM, "atim");
+    assert_time(stat.mtim, MTIM, "mtim");
+
+    wasi::path_symlink("file", dir_fd, "symlink").expect("creating symlink to a file");
+
+    // Without LOOKUPFLAGS_SYMLINK_FOLLOW, the symlink itself is changed...
+    wasi::path_filestat_set_times(
+        dir_fd,
+        0,
+        "symlink",
+        LINK_ATIM,
+        LINK_MTIM,
+        wasi::FSTFLAGS_ATIM | wasi::FSTFLAGS_MTIM,
+    )
+    .expect("path_filestat_set_times on the symlink itself");
+    let link_stat = wasi::path_filestat_get(dir_fd, 0, "symlink").expect("reading symlink stats");
+    assert_eq!(link_stat.filetype, wasi::FILETYPE_SYMBOLIC_LINK);
+    assert_time(link_stat.atim, LINK_ATIM, "symlink atim");
+    assert_time(link_stat.mtim, LINK_MTIM, "symlink mtim");
+
+    // ...and the file it points to is not.
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time(stat.atim, ATIM, "file atim after setting symlink times");
+    assert_time(stat.mtim, MTIM, "file mtim after setting symlink times");
+
+    // With it, the file is changed and the symlink is not.
+    wasi::path_filestat_set_times(
+        dir_fd,
+        wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
+        "symlink",
+        MTIM,
+        ATIM,
+        wasi::FSTFLAGS_ATIM | wasi::FSTFLAGS_MTIM,
+    )
+    .expect("path_filestat_set_times through the symlink");
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time(stat.atim, MTIM, "file atim set through symlink");
+    assert_time(stat.mtim, ATIM, "file mtim set through symlink");
+    let link_stat = wasi::path_filestat_get(dir_fd, 0, "symlink").expect("reading symlink stats");
+    assert_time(link_stat.mtim, LINK_MTIM, "symlink mtim after following it");
+
+    // Reading through the symlink sees the file's times.
+    let deref_stat = wasi::path_filestat_get(dir_fd, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW, "symlink")
+        .expect("reading file stats through the symlink");
+    assert_eq!(deref_stat.atim, stat.atim, "dereferenced atim");
+    assert_eq!(deref_stat.mtim, stat.mtim, "dereferenced mtim");
+
+    // The `_NOW` flags work by path too.
+    let before = now();
+    wasi::path_filestat_set_times(
+        dir_fd,
+        wasi::LOOKUPFLAGS_SYMLINK_FOLLOW,
+        "symlink",
+        0,
+        0,
+        wasi::FSTFLAGS_ATIM_NOW | wasi::FSTFLAGS_MTIM_NOW,
+    )
+    .expect("path_filestat_set_times with FSTFLAGS_*_NOW");
+    let after = now();
+    let stat = wasi::path_filestat_get(dir_fd, 0, "file").expect("reading file stats");
+    assert_time_between(stat.atim, before, after, "atim set to now");
+    assert_time_between(stat.mtim, before, after, "mtim set to now");
+
+    wasi::path_unlink_file(dir_fd, "symlink").expect("removing a symlink");
+}
+
+unsafe fn test_filestat_set_times(dir_fd: wasi::Fd) {
+    let file_fd = wasi::path_open(
+        dir_fd,
+        0,
+        "file",
+        wasi::OFLAGS_CREAT,
+        wasi::RIGHTS_FD_READ
+            | wasi::RIGHTS_FD_WRITE
+            | wasi::RIGHTS_FD_FILESTAT_GET
+            | wasi::RIGHTS_FD_FILESTAT_SET_TIMES,
+        0,
+        0,
+    )
+    .expect("opening a file");
+    assert!(
+        file_fd > libc::STDERR_FILENO as wasi::Fd,
+        "file descriptor range check",
+    );
+
+    test_fd_filestat_set_times(dir_fd, file_fd);
+    wasi::fd_close(file_fd).expect("closing a file");
+
+    test_path_filestat_set_times(dir_fd);
+    wasi::path_unlink_file(dir_fd, "file").expect("removing a file");
+}
+
+fn main() {
+    let base_dir_fd = match root_directory() {
+        Ok(dir_fd) => dir_fd,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+
+    const DIR_NAME: &str = "filestat_set_times_dir.cleanup";
+    let dir_fd;
+    unsafe {
+        dir_fd = create_tmp_dir(base_dir_fd, DIR_NAME);
+    }
+
+    // Run the tests.
+    unsafe { test_filestat_set_times(dir_fd) }
+
+    unsafe {
+        wasi::fd_close(dir_fd).unwrap();
+    }
+    unsafe { wasi::path_remove_directory(base_dir_fd, DIR_NAME).expect("failed to remove dir") }
+}
@@ -0,0 +1,185 @@
+use std::mem::MaybeUninit;
+use wasi_tests::assert_errno;
+use wasip1 as wasi;
+
+const SHORT_TIMEOUT: u64 = 20_000_000u64; // 20 milliseconds
+const LONG_TIMEOUT: u64 = 5_000_000_000u64; // 5 seconds, never expected to fire
+
+const CLOCK_IDS: [wasi::Clockid; 4] = [
+    wasi::CLOCKID_REALTIME,
+    wasi::CLOCKID_MONOTONIC,
+    wasi::CLOCKID_PROCESS_CPUTIME_ID,
+    wasi::CLOCKID_THREAD_CPUTIME_ID,
+];
+
+unsafe fn poll_oneoff_impl(r#in: &[wasi::Subscription]) -> Result<Vec<wasi::Event>, wasi::Errno> {
+    let mut out: Vec<wasi::Event> = Vec::new();
+    out.resize_with(r#in.len(), || {
+        MaybeUninit::<wasi::Event>::zeroed().assume_init()
+    });
+    let size = wasi::poll_oneoff(r#in.as_ptr(), out.as_mut_ptr(), r#in.len())?;
+    out.truncate(size);
+    Ok(out)
+}
+
+fn clock_sub(
+    userdata: wasi::Userdata,
+    id: wasi::Clockid,
+    timeout: wasi::Timestamp,
+    flags: wasi::Subclockflags,
+) -> wasi::Subscription {
+    wasi::Subscription {
+        userdata,
+        u: wasi::SubscriptionU {
+            tag: wasi::EVENTTYPE_CLOCK.raw(),
+            u: wasi::SubscriptionUU {
+                clock: wasi::SubscriptionClock {
+                    id,
+                    timeout,
+                    precision: 0,
+                    flags,
+                },
+            },
+        },
+    }
+}
+
+unsafe fn now(id: wasi::Clockid) -> wasi::Timestamp {
+    wasi::clock_time_get(id, 1).expect("clock_time_get")
+}
+
+fn assert_clock_event(event: &wasi::Event, userdata: wasi::Userdata) {
+    assert_eq!(event.type_, wasi::EVENTTYPE_CLOCK, "expected a clock event");
+    assert_errno!(event.error, wasi::ERRNO_SUCCESS);
+    assert_eq!(event.userdata, userdata, "unexpected event.userdata");
+}
+
+// The CPU-time clocks don't advance while the guest is blocked, so waiting on
+// them with a real timeout could hang. Runtimes may also reject them outright.
+fn is_cputime(id: wasi::Clockid) -> bool {
+    id == wasi::CLOCKID_PROCESS_CPUTIME_ID || id == wasi::CLOCKID_THREAD_CPUTIME_ID
+}
+
+unsafe fn test_zero_timeout() {
+    for (ud, id) in CLOCK_IDS.iter().enumerate() {
+        let ud = ud as wasi::Userdata;
+        match poll_oneoff_impl(&[clock_sub(ud, *id, 0, 0)]) {
+            Ok(out) => {
+                assert_eq!(out.len(), 1, "a zero timeout should fire immediately");
+                assert_clock_event(&out[0], ud);
+            }
+            Err(err) => {
+                assert!(
+                    is_cputime(*id),
+                    "zero-timeout poll on clock {:?} failed",
+                    id
+                );
+                assert_errno!(err, wasi::ERRNO_INVAL, wasi::ERRNO_NOTSUP);
+            }
+        }
+    }
+}
+
+unsafe fn test_relative_timeout() {
+    for id in CLOCK_IDS.iter().filter(|id| !is_cputime(**id)) {
+        let before = now(wasi::CLOCKID_MONOTONIC);
+        let out = poll_oneoff_impl(&[clock_sub(7, *id, SHORT_TIMEOUT, 0)]).unwrap();
+        let elapsed = now(wasi::CLOCKID_MONOTONIC) - before;
+        assert_eq!(out.len(), 1, "expected exactly one event");
+        assert_clock_event(&out[0], 7);
+        assert!(
+            elapsed >= SHORT_TIMEOUT,
+            "clock {:?} fired after {}ns, before its {}ns timeout",
+            id,
+            elapsed,
+            SHORT_TIMEOUT
+        );
+    }
+}
+
+unsafe fn test_absolute_timeout() {
+    for id in CLOCK_IDS.iter().filter(|id| !is_cputime(**id)) {
+        let deadline = now(*id) + SHORT_TIMEOUT;
+        let out = poll_oneoff_impl(&[clock_sub(
+            8,
+            *id,
+            deadline,
+            wasi::SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME,
+        )])
+        .unwrap();
+        let after = now(*id);
+        assert_eq!(out.len(), 1, "expected exactly one event");
+        assert_clock_event(&out[0], 8);
+        assert!(
+            after >= deadline,
+            "clock {:?} fired at {}, before its deadline {}",
+            id,
+            after,
+            deadline
+        );
+
+        // A deadline that has already passed fires without waiting.
+        let before = now(wasi::CLOCKID_MONOTONIC);
+        let out = poll_oneoff_impl(&[clock_sub(
+            9,
+            *id,
+            deadline,
+            wasi::SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME,
+        )])
+        .unwrap();
+        let elapsed = now(wasi::CLOCKID_MONOTONIC) - before;
+        assert_eq!(out.len(), 1, "expected exactly one event");
+        assert_clock_event(&out[0], 9);
+        assert!(
+            elapsed < LONG_TIMEOUT,
+            "a past deadline on clock {:?} should not block",
+            id
+        );
+    }
+}
+
+unsafe fn test_earliest_fires_first() {
+    // Userdata is the timeout, so every event can be checked against it.
+    let timeouts = [LONG_TIMEOUT, SHORT_TIMEOUT, 10 * SHORT_TIMEOUT];
+    let mut subs: Vec<wasi::Subscription> = timeouts
+        .iter()
+        .map(|timeout| clock_sub(*timeout, wasi::CLOCKID_MONOTONIC, *timeout, 0))
+        .collect();
+    // A realtime subscription that is also due later must not win either.
+    subs.push(clock_sub(
+        LONG_TIMEOUT + 1,
+        wasi::CLOCKID_REALTIME,
+        LONG_TIMEOUT + 1,
+        0,
+    ));
+
+    let before = now(wasi::CLOCKID_MONOTONIC);
+    let out = poll_oneoff_impl(&subs).unwrap();
+    let elapsed = now(wasi::CLOCKID_MONOTONIC) - before;
+    assert!(
+        out.iter().any(|event| event.userdata == SHORT_TIMEOUT),
+        "the earliest clock should fire first"
+    );
+    for event in out {
+        assert_eq!(event.type_, wasi::EVENTTYPE_CLOCK);
+        assert_errno!(event.error, wasi::ERRNO_SUCCESS);
+        assert!(
+            event.userdata <= elapsed,
+            "clock with timeout {}ns fired after only {}ns",
+            event.userdata,
+            elapsed
+        );
+    }
+}
+
+unsafe fn test_poll_oneoff_clock() {
+    test_zero_timeout();
+    test_relative_timeout();
+    test_absolute_timeout();
+    test_earliest_fires_first();
+}
+
+fn main() {
+    // Run the tests.
+    unsafe { test_poll_oneoff_clock() }
+}
@@ -0,0 +1,152 @@
+use std::mem::MaybeUninit;
+use std::process;
+use wasi_tests::{assert_errno, create_tmp_dir, root_directory};
+use wasip1 as wasi;
+
+const TIMEOUT: u64 = 5_000_000_000u64; // 5 seconds, never expected to fire
+const CLOCK_ID: wasi::Userdata = 0x0123_45678;
+const READ_ID: wasi::Userdata = 0x1111_11111;
+const WRITE_ID: wasi::Userdata = 0x2222_22222;
+
+unsafe fn poll_oneoff_impl(r#in: &[wasi::Subscription]) -> Result<Vec<wasi::Event>, wasi::Errno> {
+    let mut out: Vec<wasi::Event> = Vec::new();
+    out.resize_with(r#in.len(), || {
+        MaybeUninit::<wasi::Event>::zeroed().assume_init()
+    });
+    let size = wasi::poll_oneoff(r#in.as_ptr(), out.as_mut_ptr(), r#in.len())?;
+    out.truncate(size);
+    Ok(out)
+}
+
+fn clock_sub(timeout: wasi::Timestamp) -> wasi::Subscription {
+    wasi::Subscription {
+        userdata: CLOCK_ID,
+        u: wasi::SubscriptionU {
+            tag: wasi::EVENTTYPE_CLOCK.raw(),
+            u: wasi::SubscriptionUU {
+                clock: wasi::SubscriptionClock {
+                    id: wasi::CLOCKID_MONOTONIC,
+                    timeout,
+                    precision: 0,
+                    flags: 0,
+                },
+            },
+        },
+    }
+}
+
+fn fd_read_sub(fd: wasi::Fd) -> wasi::Subscription {
+    wasi::Subscription {
+        userdata: READ_ID,
+        u: wasi::SubscriptionU {
+            tag: wasi::EVENTTYPE_FD_READ.raw(),
+            u: wasi::SubscriptionUU {
+                fd_read: wasi::SubscriptionFdReadwrite {
+                    file_descriptor: fd,
+                },
+            },
+        },
+    }
+}
+
+fn fd_write_sub(fd: wasi::Fd) -> wasi::Subscription {
+    wasi::Subscription {
+        userdata: WRITE_ID,
+        u: wasi::SubscriptionU {
+            tag: wasi::EVENTTYPE_FD_WRITE.raw(),
+            u: wasi::SubscriptionUU {
+                fd_write: wasi::SubscriptionFdReadwrite {
+                    file_descriptor: fd,
+                },
+            },
+        },
+    }
+}
+
+// Regular files are always ready, so a poll mixing them with a long clock must
+// return the file events straight away and never the clock.
+unsafe fn assert_files_ready(subs: &[wasi::Subscription], expected: &[wasi::Userdata]) {
+    let out = poll_oneoff_impl(subs).unwrap();
+    let mut seen: Vec<wasi::Userdata> = Vec::new();
+    for event in out {
+        assert_ne!(
+            event.userdata, CLOCK_ID,
+            "the clock fired although files are always ready"
+        );
+        assert_errno!(event.error, wasi::ERRNO_SUCCESS);
+        match event.userdata {
+            READ_ID => assert_eq!(event.type_, wasi::EVENTTYPE_FD_READ),
+            WRITE_ID => assert_eq!(event.type_, wasi::EVENTTYPE_FD_WRITE),
+            ud => panic!("unexpected event.userdata {}", ud),
+        }
+        seen.push(event.userdata);
+    }
+    seen.sort();
+    assert_eq!(seen, expected, "every file subscription should be ready");
+}
+
+unsafe fn test_poll_oneoff_files(dir_fd: wasi::Fd) {
+    let fd = wasi::path_open(
+        dir_fd,
+        0,
+        "file",
+        wasi::OFLAGS_CREAT,
+        wasi::RIGHTS_FD_READ | wasi::RIGHTS_FD_WRITE | wasi::RIGHTS_POLL_FD_READWRITE,
+        0,
+        0,
+    )
+    .expect("opening a file");
+
+    // An empty file is ready for reading, just as one with data in it is.
+    assert_files_ready(&[fd_read_sub(fd), clock_sub(TIMEOUT)], &[READ_ID]);
+    assert_files_ready(&[clock_sub(TIMEOUT), fd_write_sub(fd)], &[WRITE_ID]);
+
+    let ciovec = wasi::Ciovec {
+        buf: b"poll".as_ptr(),
+        buf_len: 4,
+    };
+    let nwritten = wasi::fd_write(fd, &[ciovec]).expect("writing to file");
+    assert_eq!(nwritten, 4, "nwritten bytes check");
+
+    assert_files_ready(
+        &[fd_read_sub(fd), clock_sub(TIMEOUT), fd_write_sub(fd)],
+        &[READ_ID, WRITE_ID],
+    );
+
+    // Even a clock that is already due must not hide the ready files.
+    let out = poll_oneoff_impl(&[fd_read_sub(fd), fd_write_sub(fd), clock_sub(0)]).unwrap();
+    for ud in [READ_ID, WRITE_ID] {
+        assert!(
+            out.iter().any(|event| event.userdata == ud),
+            "file subscription {} should be ready alongside an expired clock",
+            ud
+        );
+    }
+
+    wasi::fd_close(fd).expect("closing file");
+    wasi::path_unlink_file(dir_fd, "file").expect("removing file");
+}
+
+fn main() {
+    let base_dir_fd = match root_directory() {
+        Ok(dir_fd) => dir_fd,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+
+    const DIR_NAME: &str = "poll_oneoff_files_dir.cleanup";
+    let dir_fd;
+    unsafe {
+        dir_fd = create_tmp_dir(base_dir_fd, DIR_NAME);
+    }
+
+    // Run the tests.
+    unsafe { test_poll_oneoff_files(dir_fd) }
+
+    unsafe {
+        wasi::fd_close(dir_fd).unwrap();
+    }
+    unsafe { wasi::path_remove_directory(base_dir_fd, DIR_NAME).expect("failed to remove dir") }
+}
@@ -0,0 +1,100 @@
+use std::{env, process};
+use wasi_tests::{STDIN_FD, STDOUT_FD, assert_errno};
+use wasip1 as wasi;
+
+const FILE_NAME: &str = "preopen.txt";
+
+/// Returns the preopened directories in descriptor order, stopping at the
+/// first descriptor that `fd_prestat_get` reports as bad.
+unsafe fn enumerate_preopens() -> Vec<(wasi::Fd, String)> {
+    let mut preopens = Vec::new();
+    for fd in 3.. {
+        let stat = match wasi::fd_prestat_get(fd) {
+            Ok(stat) => stat,
+            Err(err) => {
+                assert_errno!(err, wasi::ERRNO_BADF);
+                break;
+            }
+        };
+        assert_eq!(
+            stat.tag,
+            wasi::PREOPENTYPE_DIR.raw(),
+            "preopen {} is not a directory",
+            fd
+        );
+
+        let mut name = Vec::with_capacity(stat.u.dir.pr_name_len);
+        wasi::fd_prestat_dir_name(fd, name.as_mut_ptr(), name.capacity())
+            .expect("fd_prestat_dir_name");
+        name.set_len(stat.u.dir.pr_name_len);
+        let name = String::from_utf8(name).expect("preopen name is not valid UTF-8");
+        preopens.push((fd, name));
+    }
+    preopens
+}
+
+unsafe fn read_file(dir_fd: wasi::Fd, path: &str) -> String {
+    let fd = wasi::path_open(dir_fd, 0, path, 0, wasi::RIGHTS_FD_READ, 0, 0)
+        .unwrap_or_else(|_| panic!("opening '{}'", path));
+    let mut buf = [0; 64];
+    let iovec = wasi::Iovec {
+        buf: buf.as_mut_ptr(),
+        buf_len: buf.len(),
+    };
+    let nread = wasi::fd_read(fd, &[iovec]).expect("reading a file");
+    wasi::fd_close(fd).expect("closing a file");
+    String::from_utf8(buf[..nread].to_vec()).expect("file contents are not valid UTF-8")
+}
+
+unsafe fn test_preopens(expected: &[String]) {
+    // Stdio descriptors are never preopens.
+    for fd in [STDIN_FD, STDOUT_FD] {
+        assert_errno!(
+            wasi::fd_prestat_get(fd)
+                .err()
+                .expect("fd_prestat_get on stdio should fail"),
+            wasi::ERRNO_BADF
+        );
+    }
+
+    // Preopens follow stdio without gaps, in the order they were given.
+    let preopens = enumerate_preopens();
+    let names: Vec<&str> = preopens.iter().map(|(_, name)| name.as_str()).collect();
+    assert_eq!(names, expected, "preopen names and order");
+    for (i, (fd, _)) in preopens.iter().enumerate() {
+        assert_eq!(
+            *fd,
+            3 + i as wasi::Fd,
+            "preopen descriptors should be contiguous"
+        );
+    }
+
+    // Each preopen is a distinct directory, and paths resolve relative to it.
+    for (fd, name) in &preopens {
+        let stat = wasi::fd_fdstat_get(*fd).expect("fd_fdstat_get on a preopen");
+        assert_eq!(
+            stat.fs_filetype,
+            wasi::FILETYPE_DIRECTORY,
+            "preopen {} should be a directory",
+            name
+        );
+        assert_eq!(
+            read_file(*fd, FILE_NAME),
+            format!("{}\n", name),
+            "{} in preopen {}",
+            FILE_NAME,
+            name
+        );
+    }
+}
+
+fn main() {
+    let expected: Vec<String> = env::args().skip(1).collect();
+    if expected.is_empty() {
+        eprintln!("usage: preopens <name of each preopen, in order>...");
+        process::exit(1)
+    }
+
+    // Run the tests.
+    unsafe { test_preopens(&expected) }
+}
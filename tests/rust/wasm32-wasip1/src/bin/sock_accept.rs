@@ -0,0 +1,44 @@
+use std::process;
+use wasi_tests::{STDOUT_FD, assert_errno, preopened_socket, sock_accept_blocking, sock_send_all};
+use wasip1 as wasi;
+
+unsafe fn test_sock_accept(listener: wasi::Fd) {
+    // Accepting on something that isn't a socket fails. Runtimes which don't
+    // tell sockets apart from other files report a bad descriptor instead.
+    assert_errno!(
+        wasi::sock_accept(STDOUT_FD, 0).expect_err("sock_accept on stdout should fail"),
+        wasi::ERRNO_NOTSOCK,
+        wasi::ERRNO_BADF
+    );
+
+    let fd = sock_accept_blocking(listener);
+    assert_ne!(fd, listener, "accepted socket must be a new descriptor");
+    let stat = wasi::fd_fdstat_get(fd).expect("fd_fdstat_get on accepted socket");
+    assert_eq!(
+        stat.fs_filetype,
+        wasi::FILETYPE_SOCKET_STREAM,
+        "accepted socket should be a stream socket"
+    );
+
+    // The listener itself stays usable after an accept.
+    let stat = wasi::fd_fdstat_get(listener).expect("fd_fdstat_get on listener");
+    assert_eq!(stat.fs_filetype, wasi::FILETYPE_SOCKET_STREAM);
+
+    sock_send_all(fd, b"accepted\n");
+    wasi::fd_close(fd).expect("closing accepted socket");
+}
+
+fn main() {
+    let (listener, addr) = match preopened_socket() {
+        Ok(socket) => socket,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+    // Tell the runner where to connect.
+    println!("{}", addr);
+
+    // Run the tests.
+    unsafe { test_sock_accept(listener) }
+}
@@ -0,0 +1,56 @@
+use std::process;
+use wasi_tests::{preopened_socket, sock_accept_blocking, sock_recv_blocking, sock_send_all};
+use wasip1 as wasi;
+
+const BUF_LEN: usize = 64;
+
+// Echoes everything the runner sends until it sends a `quit` line, peeking at
+// each chunk before consuming it.
+unsafe fn test_sock_recv_send(fd: wasi::Fd) {
+    let mut received = Vec::new();
+    while !received.ends_with(b"quit\n") {
+        let mut peeked = [0; BUF_LEN];
+        let (n, ro_flags) = sock_recv_blocking(fd, &mut peeked, wasi::RIFLAGS_RECV_PEEK);
+        assert!(n > 0, "connection closed before `quit`");
+        assert_eq!(
+            ro_flags & wasi::ROFLAGS_RECV_DATA_TRUNCATED,
+            0,
+            "stream sockets never truncate"
+        );
+
+        // Peeking again sees the same bytes, since nothing was consumed.
+        let mut peeked_again = [0; BUF_LEN];
+        let (m, _) = sock_recv_blocking(fd, &mut peeked_again[..n], wasi::RIFLAGS_RECV_PEEK);
+        assert_eq!(m, n, "second peek should see the same data");
+        assert_eq!(&peeked_again[..n], &peeked[..n]);
+
+        // A real read of the same length consumes exactly the peeked bytes.
+        let mut buf = [0; BUF_LEN];
+        let (m, _) = sock_recv_blocking(fd, &mut buf[..n], 0);
+        assert_eq!(m, n, "read should return the peeked bytes");
+        assert_eq!(&buf[..n], &peeked[..n]);
+
+        sock_send_all(fd, &buf[..n]);
+        received.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(received, b"hello\nquit\n");
+}
+
+fn main() {
+    let (listener, addr) = match preopened_socket() {
+        Ok(socket) => socket,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+    // Tell the runner where to connect.
+    println!("{}", addr);
+
+    // Run the tests.
+    unsafe {
+        let fd = sock_accept_blocking(listener);
+        test_sock_recv_send(fd);
+        wasi::fd_close(fd).expect("closing accepted socket");
+    }
+}
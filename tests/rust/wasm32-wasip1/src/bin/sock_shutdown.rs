@@ -0,0 +1,57 @@
+use std::process;
+use wasi_tests::{
+    assert_errno, preopened_socket, sock_accept_blocking, sock_recv_blocking, sock_send_all,
+};
+use wasip1 as wasi;
+
+unsafe fn recv_exact(fd: wasi::Fd, len: usize) -> Vec<u8> {
+    let mut data = vec![0; len];
+    let mut filled = 0;
+    while filled < len {
+        let (n, _) = sock_recv_blocking(fd, &mut data[filled..], 0);
+        assert!(n > 0, "connection closed early");
+        filled += n;
+    }
+    data
+}
+
+unsafe fn test_sock_shutdown(fd: wasi::Fd) {
+    assert_eq!(recv_exact(fd, 4), b"bye\n");
+
+    // Shutting down the write half still delivers what was sent before.
+    sock_send_all(fd, b"done\n");
+    wasi::sock_shutdown(fd, wasi::SDFLAGS_WR).expect("shutting down the write half");
+    let ciovec = wasi::Ciovec {
+        buf: b"late\n".as_ptr(),
+        buf_len: 5,
+    };
+    assert_errno!(
+        wasi::sock_send(fd, &[ciovec], 0).expect_err("sending after SDFLAGS_WR should fail"),
+        wasi::ERRNO_PIPE
+    );
+
+    // Once the read half is shut down too, reads report end-of-stream.
+    wasi::sock_shutdown(fd, wasi::SDFLAGS_RD).expect("shutting down the read half");
+    let mut buf = [0; 16];
+    let (n, _) = sock_recv_blocking(fd, &mut buf, 0);
+    assert_eq!(n, 0, "reading after SDFLAGS_RD should see end-of-stream");
+}
+
+fn main() {
+    let (listener, addr) = match preopened_socket() {
+        Ok(socket) => socket,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    };
+    // Tell the runner where to connect.
+    println!("{}", addr);
+
+    // Run the tests.
+    unsafe {
+        let fd = sock_accept_blocking(listener);
+        test_sock_shutdown(fd);
+        wasi::fd_close(fd).expect("closing accepted socket");
+    }
+}
@@ -2,6 +2,7 @@ pub struct TestConfig {
     errno_mode: ErrnoMode,
     no_dangling_filesystem: bool,
     no_rename_dir_to_empty_dir: bool,
+    timestamp_granularity_ns: u64,
 }
 
 enum ErrnoMode {
@@ -24,11 +25,18 @@ impl TestConfig {
         };
         let no_dangling_filesystem = std::env::var("NO_DANGLING_FILESYSTEM").is_ok();
         let no_rename_dir_to_empty_dir = std::env::var("NO_RENAME_DIR_TO_EMPTY_DIR").is_ok();
+        let timestamp_granularity_ns = match std::env::var("TIMESTAMP_GRANULARITY_NS") {
+            Ok(ns) => ns
+                .parse()
+                .expect("TIMESTAMP_GRANULARITY_NS should be a number of nanoseconds"),
+            Err(_) => 1,
+        };
 
         TestConfig {
             errno_mode,
             no_dangling_filesystem,
             no_rename_dir_to_empty_dir,
+            timestamp_granularity_ns,
         }
     }
     pub fn errno_expect_unix(&self) -> bool {
@@ -55,4 +63,7 @@ impl TestConfig {
     pub fn support_rename_dir_to_empty_dir(&self) -> bool {
         !self.no_rename_dir_to_empty_dir
     }
+    pub fn timestamp_granularity(&self) -> u64 {
+        self.timestamp_granularity_ns.max(1)
+    }
 }
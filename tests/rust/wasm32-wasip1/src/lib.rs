@@ -47,6 +47,7 @@ pub unsafe fn create_tmp_dir(dir_fd: wasi::Fd, name: &str) -> wasi::Fd {
             | wasi::RIGHTS_PATH_FILESTAT_GET
             | wasi::RIGHTS_FD_FDSTAT_SET_FLAGS
             | wasi::RIGHTS_FD_SYNC
+            | wasi::RIGHTS_FD_DATASYNC
             | wasi::RIGHTS_FD_TELL
             | wasi::RIGHTS_FD_ADVISE
             | wasi::RIGHTS_FD_ALLOCATE
@@ -184,3 +185,95 @@ pub unsafe fn supports_rights(fd: wasi::Fd) -> bool {
         _ => true,
     }
 }
+
+/// Finds the listening socket preopened through the runner's `tcplisten`
+/// option and returns it together with the `<host>:<port>` it is bound to.
+pub fn preopened_socket() -> Result<(wasi::Fd, String), String> {
+    let addr = std::env::var("TCP_LISTEN_ADDR")
+        .map_err(|_| format!("TCP_LISTEN_ADDR must be set by the test runner"))?;
+    unsafe {
+        for i in 3.. {
+            let stat = match wasi::fd_fdstat_get(i) {
+                Ok(s) => s,
+                Err(_) => break,
+            };
+            if stat.fs_filetype == wasi::FILETYPE_SOCKET_STREAM {
+                return Ok((i, addr));
+            }
+        }
+    }
+
+    Err(format!("failed to find preopened socket"))
+}
+
+/// Blocks until `fd` is ready for `eventtype`, which is either
+/// `EVENTTYPE_FD_READ` or `EVENTTYPE_FD_WRITE`.
+pub unsafe fn poll_fd(fd: wasi::Fd, eventtype: wasi::Eventtype) {
+    let fd_readwrite = wasi::SubscriptionFdReadwrite {
+        file_descriptor: fd,
+    };
+    let u = if eventtype == wasi::EVENTTYPE_FD_READ {
+        wasi::SubscriptionUU {
+            fd_read: fd_readwrite,
+        }
+    } else {
+        wasi::SubscriptionUU {
+            fd_write: fd_readwrite,
+        }
+    };
+    let sub = wasi::Subscription {
+        userdata: 0,
+        u: wasi::SubscriptionU {
+            tag: eventtype.raw(),
+            u,
+        },
+    };
+    let mut event = std::mem::MaybeUninit::<wasi::Event>::zeroed().assume_init();
+    let n = wasi::poll_oneoff(&sub, &mut event, 1).expect("poll_oneoff on socket");
+    assert_eq!(n, 1, "poll_oneoff should return one event");
+    assert_eq!(event.type_, eventtype);
+    assert_errno!(event.error, wasi::ERRNO_SUCCESS);
+}
+
+pub unsafe fn sock_accept_blocking(listener: wasi::Fd) -> wasi::Fd {
+    loop {
+        match wasi::sock_accept(listener, 0) {
+            Ok(fd) => return fd,
+            Err(wasi::ERRNO_AGAIN) => poll_fd(listener, wasi::EVENTTYPE_FD_READ),
+            Err(e) => panic!("sock_accept failed: {}", e.name()),
+        }
+    }
+}
+
+// Returns: (bytes received, ro_flags)
+pub unsafe fn sock_recv_blocking(
+    fd: wasi::Fd,
+    buf: &mut [u8],
+    flags: wasi::Riflags,
+) -> (usize, wasi::Roflags) {
+    let iovec = wasi::Iovec {
+        buf: buf.as_mut_ptr(),
+        buf_len: buf.len(),
+    };
+    loop {
+        match wasi::sock_recv(fd, &[iovec], flags) {
+            Ok(received) => return received,
+            Err(wasi::ERRNO_AGAIN) => poll_fd(fd, wasi::EVENTTYPE_FD_READ),
+            Err(e) => panic!("sock_recv failed: {}", e.name()),
+        }
+    }
+}
+
+pub unsafe fn sock_send_all(fd: wasi::Fd, mut data: &[u8]) {
+    while !data.is_empty() {
+        let ciovec = wasi::Ciovec {
+            buf: data.as_ptr(),
+            buf_len: data.len(),
+        };
+        match wasi::sock_send(fd, &[ciovec], 0) {
+            Ok(sent) => data = &data[sent..],
+            Err(wasi::ERRNO_AGAIN) => poll_fd(fd, wasi::EVENTTYPE_FD_WRITE),
+            Err(e) => panic!("sock_send failed: {}", e.name()),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::process;
+use test_wasm32_wasip3::cli::{
+    export,
+    exports::wasi::cli::run::Guest,
+    wasi::cli::{environment, exit::exit_with_code},
+};
+
+struct Component;
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        let args = environment::get_arguments();
+        let Some(code) = args.get(1).and_then(|arg| arg.parse::<u8>().ok()) else {
+            eprintln!("usage: cli-exit-with-code <0-255>");
+            process::exit(2)
+        };
+        exit_with_code(code);
+        unreachable!("exit-with-code returned");
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,69 @@
+use test_wasm32_wasip3::Checksum;
+use test_wasm32_wasip3::cli::{
+    export,
+    exports::wasi::cli::run::Guest,
+    wasi::cli::{stdin, stdout},
+    wit_stream,
+};
+
+use wit_bindgen::StreamResult;
+
+// Small next to the input, so that hosts have to hand it over in many pieces.
+const CHUNK: usize = 64 * 1024;
+
+/// Reads stdin to end-of-file, returning how many bytes it held and their
+/// checksum. Nothing is kept beyond the chunk being processed.
+async fn checksum_stdin() -> (u64, u64) {
+    let (mut rx, result) = stdin::read_via_stream();
+    let mut checksum = Checksum::new();
+    let mut total = 0;
+    loop {
+        let (status, chunk) = rx.read(Vec::with_capacity(CHUNK)).await;
+        match status {
+            StreamResult::Complete(n) => {
+                assert!(n <= CHUNK, "read {n} bytes into a {CHUNK} byte buffer");
+                assert_eq!(chunk.len(), n);
+                checksum.update(&chunk);
+                total += n as u64;
+            }
+            StreamResult::Dropped => {
+                // https://github.com/bytecodealliance/wit-bindgen/issues/1396
+                checksum.update(&chunk);
+                total += chunk.len() as u64;
+                break;
+            }
+            StreamResult::Cancelled => panic!("who cancelled the stream?"),
+        }
+    }
+    drop(rx);
+    // End-of-file is not an error.
+    result.await.unwrap();
+    (total, checksum.0)
+}
+
+struct Component;
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        let (total, checksum) = checksum_stdin().await;
+
+        let (mut tx, rx) = wit_stream::new();
+        futures::join!(
+            async {
+                stdout::write_via_stream(rx).await.unwrap();
+            },
+            async {
+                let line = format!("{total} {checksum:016x}\n");
+                let remaining = tx.write_all(line.into_bytes()).await;
+                assert!(remaining.is_empty());
+                drop(tx);
+            }
+        );
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
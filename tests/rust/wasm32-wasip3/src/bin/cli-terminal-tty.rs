@@ -0,0 +1,47 @@
+use std::process;
+use test_wasm32_wasip3::cli::{
+    export,
+    exports::wasi::cli::run::Guest,
+    wasi::cli::{environment, terminal_stderr, terminal_stdin, terminal_stdout},
+};
+
+const STREAMS: [&str; 3] = ["stdin", "stdout", "stderr"];
+
+fn is_terminal(stream: &str) -> bool {
+    match stream {
+        "stdin" => terminal_stdin::get_terminal_stdin().is_some(),
+        "stdout" => terminal_stdout::get_terminal_stdout().is_some(),
+        "stderr" => terminal_stderr::get_terminal_stderr().is_some(),
+        _ => unreachable!(),
+    }
+}
+
+struct Component;
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        // The streams which the host attached to a terminal.
+        let expected: Vec<String> = environment::get_arguments().into_iter().skip(1).collect();
+        if let Some(arg) = expected.iter().find(|arg| !STREAMS.contains(&arg.as_str())) {
+            eprintln!("usage: cli-terminal-tty [stdin] [stdout] [stderr], got {arg}");
+            process::exit(2)
+        }
+
+        for stream in STREAMS {
+            let terminal = expected.iter().any(|arg| arg == stream);
+            assert_eq!(is_terminal(stream), terminal, "{stream} is a terminal");
+            // Asking again gives the same answer.
+            assert_eq!(
+                is_terminal(stream),
+                terminal,
+                "{stream} is still a terminal"
+            );
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,88 @@
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest, wasi::cli::environment};
+use test_wasm32_wasip3::clocks::{
+    DAY, HOUR,
+    wasi::clocks::{system_clock::Instant, timezone},
+};
+
+// America/New_York switched to daylight saving time at 2024-03-10T07:00:00Z
+// and back at 2024-11-03T06:00:00Z.
+const DST_START: i64 = 1_710_054_000;
+const DST_END: i64 = 1_730_613_600;
+const EST: i64 = -5 * HOUR as i64;
+const EDT: i64 = -4 * HOUR as i64;
+
+fn configured_tz() -> String {
+    environment::get_environment()
+        .into_iter()
+        .find_map(|(k, v)| (k == "TZ").then_some(v))
+        .expect("TZ should be set")
+}
+
+fn test_iana_id(expected: &str) -> bool {
+    let id = timezone::iana_id();
+    // The answer doesn't change from one call to the next.
+    assert_eq!(timezone::iana_id(), id);
+    match id {
+        Some(id) => {
+            assert_eq!(id, expected, "iana-id should be the configured zone");
+            true
+        }
+        None => false,
+    }
+}
+
+fn test_utc_offset(has_iana_id: bool) {
+    // Fractions of a second don't change which side of a switch an instant
+    // is on.
+    let instants = [
+        (DST_START - 1, 999_999_999, EST),
+        (DST_START, 0, EDT),
+        (DST_END - 1, 999_999_999, EDT),
+        (DST_END, 0, EST),
+    ];
+    for (seconds, nanoseconds, expected) in instants {
+        let when = Instant {
+            seconds,
+            nanoseconds,
+        };
+        match timezone::utc_offset(when) {
+            Some(offset) => {
+                assert!(
+                    offset.unsigned_abs() < DAY,
+                    "utc-offset should be less than a day"
+                );
+                assert_eq!(offset, expected, "utc-offset at {}s", seconds);
+            }
+            // Having no mapping to UTC also rules out an IANA identifier, and
+            // the configured zone has one.
+            None => assert!(
+                !has_iana_id,
+                "utc-offset at {}s is unknown, but iana-id is not",
+                seconds
+            ),
+        }
+    }
+}
+
+fn test_to_debug_string() {
+    // The format is unspecified, so only check that it is always available
+    // and stable.
+    let s = timezone::to_debug_string();
+    assert_eq!(timezone::to_debug_string(), s);
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        let tz = configured_tz();
+        let has_iana_id = test_iana_id(&tz);
+        test_utc_offset(has_iana_id);
+        test_to_debug_string();
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
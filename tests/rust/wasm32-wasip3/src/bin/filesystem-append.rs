@@ -0,0 +1,179 @@
+use futures::join;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, ErrorCode, OpenFlags, PathFlags,
+};
+use test_wasm32_wasip3::filesystem::{
+    self, create_scratch_dir, pwrite, read_file, remove_tree, wit_stream, write_file,
+};
+
+const DIR_NAME: &str = "filesystem-append.cleanup";
+// Large enough that concurrent appends take several transfers each.
+const APPEND_LEN: usize = 128 * 1024;
+
+async fn open(dir: &Descriptor, path: &str, flags: DescriptorFlags) -> Descriptor {
+    dir.open_at(
+        PathFlags::empty(),
+        path.to_string(),
+        OpenFlags::empty(),
+        flags,
+    )
+    .await
+    .unwrap()
+}
+
+/// Appends all of `data` through one stream. Unlike `filesystem::pappend`,
+/// this makes no assumptions about the file's size, which other streams may
+/// be changing at the same time.
+async fn append(fd: &Descriptor, data: Vec<u8>) -> Result<(), ErrorCode> {
+    let (mut tx, rx) = wit_stream::new();
+    let (result, remaining) = join!(fd.append_via_stream(rx), async {
+        let remaining = tx.write_all(data).await;
+        drop(tx);
+        remaining
+    });
+    result?;
+    assert!(
+        remaining.is_empty(),
+        "append succeeded without taking all data"
+    );
+    Ok(())
+}
+
+async fn test_sequential(dir: &Descriptor) {
+    write_file(dir, "sequential", b"").await.unwrap();
+    let fd = open(dir, "sequential", DescriptorFlags::WRITE).await;
+
+    // Each stream starts at whatever the end of the file is by then.
+    let mut expected = Vec::new();
+    for i in 0..5 {
+        let record = format!("record {i}\n").into_bytes();
+        append(&fd, record.clone()).await.unwrap();
+        expected.extend(record);
+        assert_eq!(read_file(dir, "sequential").await.unwrap(), expected);
+    }
+
+    // Appending nothing changes nothing.
+    append(&fd, Vec::new()).await.unwrap();
+    assert_eq!(read_file(dir, "sequential").await.unwrap(), expected);
+
+    // Appends from another descriptor, and from this one again, go after
+    // each other.
+    let other = open(dir, "sequential", DescriptorFlags::WRITE).await;
+    append(&other, b"other\n".to_vec()).await.unwrap();
+    append(&fd, b"again\n".to_vec()).await.unwrap();
+    expected.extend(b"other\nagain\n");
+    assert_eq!(read_file(dir, "sequential").await.unwrap(), expected);
+}
+
+async fn test_concurrent(dir: &Descriptor) {
+    write_file(dir, "concurrent", b"").await.unwrap();
+    let fd = open(dir, "concurrent", DescriptorFlags::WRITE).await;
+
+    // Each stream appends its own byte value. The host may split streams
+    // into any number of writes, so their data can interleave, but every byte
+    // must land at the end of the file without overwriting anyone else's.
+    let (a, b, c, d) = join!(
+        append(&fd, vec![b'a'; APPEND_LEN]),
+        append(&fd, vec![b'b'; APPEND_LEN]),
+        append(&fd, vec![b'c'; APPEND_LEN]),
+        append(&fd, vec![b'd'; APPEND_LEN]),
+    );
+    for r in [a, b, c, d] {
+        r.unwrap();
+    }
+
+    let contents = read_file(dir, "concurrent").await.unwrap();
+    assert_eq!(
+        contents.len(),
+        4 * APPEND_LEN,
+        "size after concurrent appends"
+    );
+    for value in [b'a', b'b', b'c', b'd'] {
+        let count = contents.iter().filter(|&&byte| byte == value).count();
+        assert_eq!(
+            count, APPEND_LEN,
+            "number of '{}' bytes after concurrent appends",
+            value as char
+        );
+    }
+}
+
+async fn test_with_positional_writes(dir: &Descriptor) {
+    const HEADER: &[u8] = b"header: unset\n";
+    const NEW_HEADER: &[u8] = b"header: valid\n";
+    write_file(dir, "mixed", HEADER).await.unwrap();
+    let fd = open(dir, "mixed", DescriptorFlags::READ | DescriptorFlags::WRITE).await;
+
+    // A positional write neither moves the end of the file for appends nor
+    // gets moved there itself.
+    append(&fd, b"one\n".to_vec()).await.unwrap();
+    assert_eq!(pwrite(&fd, 0, NEW_HEADER).await, Ok(NEW_HEADER.len()));
+    append(&fd, b"two\n".to_vec()).await.unwrap();
+    assert_eq!(
+        read_file(dir, "mixed").await.unwrap(),
+        [NEW_HEADER, &b"one\ntwo\n"[..]].concat()
+    );
+
+    // The same holds while both are in flight.
+    let body = vec![b'x'; APPEND_LEN];
+    let (appended, written) = join!(append(&fd, body.clone()), pwrite(&fd, 0, HEADER));
+    appended.unwrap();
+    assert_eq!(written, Ok(HEADER.len()));
+    assert_eq!(
+        read_file(dir, "mixed").await.unwrap(),
+        [HEADER, &b"one\ntwo\n"[..], &body[..]].concat()
+    );
+
+    // Writing at an offset past the end extends the file, and later appends
+    // follow the new end.
+    let end = (HEADER.len() + 8 + APPEND_LEN) as u64;
+    assert_eq!(pwrite(&fd, end + 4, b"gap\n").await, Ok(4));
+    append(&fd, b"tail\n".to_vec()).await.unwrap();
+    assert_eq!(
+        read_file(dir, "mixed").await.unwrap(),
+        [
+            HEADER,
+            &b"one\ntwo\n"[..],
+            &body[..],
+            &b"\0\0\0\0gap\ntail\n"[..]
+        ]
+        .concat()
+    );
+}
+
+async fn test_read_only(dir: &Descriptor) {
+    write_file(dir, "read-only", b"unchanged\n").await.unwrap();
+    let fd = open(dir, "read-only", DescriptorFlags::READ).await;
+    let result = append(&fd, b"appended\n".to_vec()).await;
+    assert!(
+        result.is_err(),
+        "appending to a read-only descriptor: {result:?}"
+    );
+    assert_eq!(read_file(dir, "read-only").await.unwrap(), b"unchanged\n");
+}
+
+async fn test_append(root: &Descriptor) {
+    let dir = create_scratch_dir(root, DIR_NAME).await;
+
+    test_sequential(&dir).await;
+    test_concurrent(&dir).await;
+    test_with_positional_writes(&dir).await;
+    test_read_only(&dir).await;
+
+    drop(dir);
+    remove_tree(root, DIR_NAME).await;
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_append(&filesystem::root()).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
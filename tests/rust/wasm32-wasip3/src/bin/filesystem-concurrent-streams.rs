@@ -0,0 +1,247 @@
+use futures::join;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, OpenFlags, PathFlags,
+};
+use test_wasm32_wasip3::filesystem::{
+    self, create_scratch_dir, pread, pwrite, read_file, read_to_eof, remove_tree, wit_stream,
+};
+use wit_bindgen::StreamResult;
+
+const DIR_NAME: &str = "filesystem-concurrent-streams.cleanup";
+// Large enough that no stream finishes in a single transfer.
+const REGION: usize = 256 * 1024;
+const CHUNK: usize = 4096;
+
+/// Contents of region `i` in generation `generation`, distinct per byte
+/// position so that misplaced or interleaved data can't go unnoticed.
+fn pattern(i: usize, generation: u8) -> Vec<u8> {
+    (0..REGION)
+        .map(|n| (n % 241) as u8 ^ (i as u8 * 61) ^ generation)
+        .collect()
+}
+
+fn assert_region(data: &[u8], i: usize, generation: u8, what: &str) {
+    let expected = pattern(i, generation);
+    assert_eq!(data.len(), expected.len(), "length of {what}");
+    if let Some(n) = data.iter().zip(&expected).position(|(a, b)| a != b) {
+        panic!("{what} differs at byte {n}");
+    }
+}
+
+async fn open_rw(dir: &Descriptor, path: &str) -> Descriptor {
+    dir.open_at(
+        PathFlags::empty(),
+        path.to_string(),
+        OpenFlags::CREATE,
+        DescriptorFlags::READ | DescriptorFlags::WRITE,
+    )
+    .await
+    .unwrap()
+}
+
+fn offset(i: usize) -> u64 {
+    (i * REGION) as u64
+}
+
+async fn test_concurrent_writes(fd: &Descriptor) {
+    let (a, b, c, d) = (pattern(0, 0), pattern(1, 0), pattern(2, 0), pattern(3, 0));
+    // Started out of order, so the file grows from the far end first.
+    let (rd, rb, ra, rc) = join!(
+        pwrite(fd, offset(3), &d),
+        pwrite(fd, offset(1), &b),
+        pwrite(fd, offset(0), &a),
+        pwrite(fd, offset(2), &c),
+    );
+    for r in [ra, rb, rc, rd] {
+        assert_eq!(r, Ok(REGION));
+    }
+    assert_eq!(fd.stat().await.unwrap().size, offset(4));
+}
+
+async fn test_concurrent_reads(fd: &Descriptor) {
+    let (r2, r0, r3, r1, whole) = join!(
+        pread(fd, REGION, offset(2)),
+        pread(fd, REGION, offset(0)),
+        pread(fd, REGION, offset(3)),
+        pread(fd, REGION, offset(1)),
+        read_to_eof(fd, 0),
+    );
+    for (i, r) in [r0, r1, r2, r3].into_iter().enumerate() {
+        assert_region(&r.unwrap(), i, 0, &format!("region {i}"));
+    }
+    assert_eq!(whole.len(), 4 * REGION);
+    for i in 0..4 {
+        assert_region(
+            &whole[i * REGION..][..REGION],
+            i,
+            0,
+            &format!("region {i} of the whole file"),
+        );
+    }
+}
+
+async fn test_reads_during_writes(fd: &Descriptor) {
+    // Writers rewrite the even regions while readers look at the odd ones,
+    // which must come back unchanged.
+    let (new0, new2) = (pattern(0, 1), pattern(2, 1));
+    let (w0, r1, w2, r3) = join!(
+        pwrite(fd, offset(0), &new0),
+        pread(fd, REGION, offset(1)),
+        pwrite(fd, offset(2), &new2),
+        pread(fd, REGION, offset(3)),
+    );
+    assert_eq!(w0, Ok(REGION));
+    assert_eq!(w2, Ok(REGION));
+    assert_region(&r1.unwrap(), 1, 0, "region 1 read during writes");
+    assert_region(&r3.unwrap(), 3, 0, "region 3 read during writes");
+
+    let whole = read_to_eof(fd, 0).await;
+    for (i, generation) in [(0, 1), (1, 0), (2, 1), (3, 0)] {
+        assert_region(
+            &whole[i * REGION..][..REGION],
+            i,
+            generation,
+            &format!("region {i} after writes"),
+        );
+    }
+}
+
+async fn test_interleaved_chunks(fd: &Descriptor) {
+    // Feed two write streams one chunk at a time, alternating between them,
+    // so their transfers are guaranteed to overlap.
+    let (mut tx1, rx1) = wit_stream::new();
+    let (mut tx3, rx3) = wit_stream::new();
+    let done1 = fd.write_via_stream(rx1, offset(1));
+    let done3 = fd.write_via_stream(rx3, offset(3));
+    let (new1, new3) = (pattern(1, 2), pattern(3, 2));
+
+    let ((), r1, r3) = join!(
+        async {
+            for (c1, c3) in new1.chunks(CHUNK).zip(new3.chunks(CHUNK)) {
+                assert!(tx1.write_all(c1.to_vec()).await.is_empty());
+                assert!(tx3.write_all(c3.to_vec()).await.is_empty());
+            }
+            drop(tx1);
+            drop(tx3);
+        },
+        done1,
+        done3,
+    );
+    r1.unwrap();
+    r3.unwrap();
+
+    let whole = read_to_eof(fd, 0).await;
+    assert_eq!(whole.len(), 4 * REGION);
+    for (i, generation) in [(0, 1), (1, 2), (2, 1), (3, 2)] {
+        assert_region(
+            &whole[i * REGION..][..REGION],
+            i,
+            generation,
+            &format!("region {i} after interleaved writes"),
+        );
+    }
+}
+
+async fn test_drop_descriptor(dir: &Descriptor) {
+    let fd = open_rw(dir, "dropped").await;
+    let data = pattern(0, 3);
+    assert_eq!(pwrite(&fd, 0, &data).await, Ok(REGION));
+
+    let (mut rx, read_done) = fd.read_via_stream(0);
+    let (mut tx, wx) = wit_stream::new();
+    let write_done = fd.write_via_stream(wx, offset(1));
+    drop(fd);
+
+    // Streams that were already open may run to completion, or stop early
+    // and say so through their futures. Either way, what arrives is correct.
+    let (read, (sent, write_result)) = join!(
+        async {
+            // The write below may extend the file while this runs, so never
+            // ask for anything past the end of the first region.
+            let mut read = Vec::new();
+            while read.len() < REGION {
+                let want = CHUNK.min(REGION - read.len());
+                let (result, chunk) = rx.read(Vec::with_capacity(want)).await;
+                match result {
+                    StreamResult::Complete(_) => read.extend(chunk),
+                    StreamResult::Dropped => break,
+                    StreamResult::Cancelled => panic!("who cancelled the stream?"),
+                }
+            }
+            drop(rx);
+            read
+        },
+        async {
+            let mut sent = 0;
+            let (mut result, mut buf) = tx.write(data.clone()).await;
+            loop {
+                match result {
+                    StreamResult::Complete(n) => {
+                        sent += n;
+                        if buf.remaining() == 0 {
+                            break;
+                        }
+                        (result, buf) = tx.write_buf(buf).await;
+                    }
+                    StreamResult::Dropped => break,
+                    StreamResult::Cancelled => panic!("who cancelled the stream?"),
+                }
+            }
+            drop(tx);
+            (sent, write_done.await)
+        },
+    );
+
+    match read_done.await {
+        Ok(()) => assert_region(&read, 0, 3, "read after dropping the descriptor"),
+        Err(err) => {
+            eprintln!("read after dropping the descriptor failed: {err:?}");
+            assert!(read.len() <= REGION, "read too much");
+            assert!(data.starts_with(&read), "read the wrong bytes");
+        }
+    }
+
+    let contents = read_file(dir, "dropped").await.unwrap();
+    assert!(contents.starts_with(&data), "existing contents changed");
+    let written = &contents[REGION..];
+    match write_result {
+        Ok(()) => {
+            assert_eq!(sent, REGION, "write finished without taking everything");
+            assert_region(written, 0, 3, "write after dropping the descriptor");
+        }
+        Err(err) => {
+            eprintln!("write after dropping the descriptor failed: {err:?}");
+            assert!(written.len() <= sent, "more was written than was sent");
+            assert!(data.starts_with(written), "wrote the wrong bytes");
+        }
+    }
+}
+
+async fn test_concurrent_streams(root: &Descriptor) {
+    let dir = create_scratch_dir(root, DIR_NAME).await;
+
+    let fd = open_rw(&dir, "file").await;
+    test_concurrent_writes(&fd).await;
+    test_concurrent_reads(&fd).await;
+    test_reads_during_writes(&fd).await;
+    test_interleaved_chunks(&fd).await;
+    drop(fd);
+    test_drop_descriptor(&dir).await;
+
+    drop(dir);
+    remove_tree(root, DIR_NAME).await;
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_concurrent_streams(&filesystem::root()).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -1,154 +1,8 @@
-use futures::join;
-use std::process;
-extern crate wit_bindgen;
-
-wit_bindgen::generate!({
-    inline: r"
-  package test:test;
-
-  world test {
-      include wasi:filesystem/imports@0.3.0;
-      include wasi:cli/command@0.3.0;
-  }
-",
-    additional_derives: [PartialEq, Eq, Hash, Clone],
-    // Work around https://github.com/bytecodealliance/wasm-tools/issues/2285.
-    features:["clocks-timezone"],
-    generate_all
-});
-
-use wasi::filesystem::types::Descriptor;
-use wasi::filesystem::types::{DescriptorFlags, ErrorCode, OpenFlags, PathFlags};
-use wit_bindgen::StreamResult;
-
-async fn pread(fd: &Descriptor, size: usize, offset: u64) -> Result<Vec<u8>, ErrorCode> {
-    let (mut rx, future) = fd.read_via_stream(offset);
-    let data = Vec::<u8>::with_capacity(size);
-    let mut bytes_read = 0;
-    let (mut result, mut data) = rx.read(data).await;
-    loop {
-        match result {
-            StreamResult::Complete(n) => {
-                assert!(n <= size - bytes_read);
-                bytes_read += n;
-                assert_eq!(data.len(), bytes_read);
-                if bytes_read == size {
-                    break;
-                }
-                (result, data) = rx.read(data).await;
-            }
-            StreamResult::Dropped => {
-                // https://github.com/bytecodealliance/wit-bindgen/issues/1396
-                assert!(data.len() >= bytes_read);
-                break;
-            }
-            StreamResult::Cancelled => {
-                panic!("who cancelled the stream?");
-            }
-        }
-    }
-    drop(rx);
-    match future.await {
-        Ok(()) => Ok(data),
-        Err(err) => Err(err),
-    }
-}
-
-async fn pwrite(fd: &Descriptor, offset: u64, data: &[u8]) -> Result<usize, ErrorCode> {
-    let (mut tx, rx) = wit_stream::new();
-    let future = fd.write_via_stream(rx, offset);
-    let len = data.len();
-    let mut written: usize = 0;
-    let mut result: Result<(), ErrorCode> = Ok(());
-    join! {
-        async {
-            let (mut result, mut buf) = tx.write(data.to_vec()).await;
-            loop {
-                match result {
-                    StreamResult::Complete(n) => {
-                        assert!(n <= len - written);
-                        written += n;
-                        assert_eq!(buf.remaining(), len - written);
-                        if buf.remaining() != 0 {
-                            (result, buf) = tx.write_buf(buf).await;
-                        } else {
-                            break;
-                        }
-                    }
-                    StreamResult::Dropped => {
-                        // https://github.com/bytecodealliance/wit-bindgen/issues/1396
-                        assert!(buf.remaining() <= len - written);
-                        panic!("receiver dropped the stream?");
-                    }
-                    StreamResult::Cancelled => {
-                        break;
-                    }
-                }
-            }
-            assert_eq!(buf.remaining(), len - written);
-            drop(tx);
-        },
-        async { result = future.await; }
-    };
-    match result {
-        Ok(()) => Ok(written),
-        Err(err) => Err(err),
-    }
-}
-
-async fn pappend(fd: &Descriptor, data: &[u8]) -> Result<usize, ErrorCode> {
-    let (mut tx, rx) = wit_stream::new();
-    let future = fd.append_via_stream(rx);
-    let initial_size = fd.stat().await.unwrap().size as usize;
-    let len = data.len();
-    let mut written: usize = 0;
-    let mut result: Result<(), ErrorCode> = Ok(());
-    join! {
-        async {
-            let (mut result, mut buf) = tx.write(data.to_vec()).await;
-            loop {
-                match result {
-                    StreamResult::Complete(n) => {
-                        assert!(n <= len - written);
-                        written += n;
-                        assert_eq!(buf.remaining(), len - written);
-                        if buf.remaining() != 0 {
-                            (result, buf) = tx.write_buf(buf).await;
-                        } else {
-                            break;
-                        }
-                    }
-                    StreamResult::Dropped => {
-                        panic!("receiver dropped the stream?");
-                    }
-                    StreamResult::Cancelled => {
-                        break;
-                    }
-                }
-            }
-            assert_eq!(buf.remaining(), len - written);
-            drop(tx);
-        },
-        async { result = future.await; }
-    };
-    match result {
-        Ok(()) => {
-            assert_eq!(
-                fd.stat().await.unwrap().size as usize,
-                initial_size + written
-            );
-            Ok(written)
-        }
-        Err(err) => Err(err),
-    }
-}
-
-async fn read_to_eof(fd: &Descriptor, offset: u64) -> Vec<u8> {
-    let (stream, success) = fd.read_via_stream(offset);
-    let ret = stream.collect().await;
-    success.await.unwrap();
-    ret
-}
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, ErrorCode, OpenFlags, PathFlags,
+};
+use test_wasm32_wasip3::filesystem::{self, pappend, pread, pwrite, read_to_eof};
 
 async fn test_io(dir: &Descriptor) {
     let open = |path: &str, oflags: OpenFlags, fdflags: DescriptorFlags| -> _ {
@@ -214,17 +68,9 @@ async fn test_io(dir: &Descriptor) {
 
 struct Component;
 export!(Component);
-impl exports::wasi::cli::run::Guest for Component {
+impl Guest for Component {
     async fn run() -> Result<(), ()> {
-        match &wasi::filesystem::preopens::get_directories()[..] {
-            [(dir, _)] => {
-                test_io(dir).await;
-            }
-            [..] => {
-                eprintln!("usage: run with one open dir");
-                process::exit(1)
-            }
-        };
+        test_io(&filesystem::root()).await;
         Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use std::process;
+use test_wasm32_wasip3::assert_error_code;
 extern crate wit_bindgen;
 
 wit_bindgen::generate!({
@@ -36,23 +37,28 @@ async fn test_open_errors(dir: &Descriptor) {
     let open_r = |path: &str| open(PathFlags::empty(), path);
     let open_r_follow = |path: &str| open(PathFlags::SYMLINK_FOLLOW, path);
     // open-at: async func(path-flags: path-flags, path: string, open-flags: open-flags, %flags: descriptor-flags) -> result<descriptor, error-code>;
-    assert_eq!(open_r("").await.expect_err("open"), ErrorCode::NoEntry);
-    assert_eq!(
+    assert_error_code!(open_r("").await.expect_err("open"), ErrorCode::NoEntry);
+    assert_error_code!(
         open_r("..").await.expect_err("open .."),
         ErrorCode::NotPermitted
     );
     if has_symlink {
-        assert_eq!(
+        assert_error_code!(
             open_r_follow("parent.cleanup")
                 .await
                 .expect_err("open parent.cleanup"),
             ErrorCode::NotPermitted
         );
     }
-    assert_eq!(
+    assert_error_code!(
         open_r("/").await.expect_err("open /"),
         ErrorCode::NotPermitted
     );
+    assert_error_code!(
+        open_r("a.txt/").await.expect_err("open a.txt/"),
+        unix => ErrorCode::NotDirectory,
+        windows => ErrorCode::NoEntry
+    );
 }
 
 struct Component;
@@ -0,0 +1,135 @@
+use std::process;
+use test_wasm32_wasip3::assert_error_code;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest, wasi::cli::environment};
+use test_wasm32_wasip3::filesystem::read_file;
+use test_wasm32_wasip3::filesystem::wasi::filesystem::preopens;
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorType, ErrorCode, PathFlags,
+};
+
+const FILE_NAME: &str = "preopen.txt";
+
+async fn test_names_and_contents(preopens: &[(Descriptor, String)], expected: &[String]) {
+    let names: Vec<&str> = preopens.iter().map(|(_, name)| name.as_str()).collect();
+    assert_eq!(names, expected, "preopen names and order");
+
+    // Each name refers to its own directory.
+    for (dir, name) in preopens {
+        assert_eq!(
+            dir.get_type().await,
+            Ok(DescriptorType::Directory),
+            "type of {name}"
+        );
+        assert_eq!(
+            read_file(dir, FILE_NAME).await.unwrap(),
+            format!("{name}\n").into_bytes(),
+            "{FILE_NAME} in {name}"
+        );
+    }
+}
+
+async fn test_independent(preopens: &[(Descriptor, String)]) {
+    for (i, (dir, name)) in preopens.iter().enumerate() {
+        for (j, (other, other_name)) in preopens.iter().enumerate() {
+            assert_eq!(
+                dir.is_same_object(other).await,
+                i == j,
+                "{name} and {other_name}"
+            );
+        }
+    }
+
+    // Asking again hands out new descriptors for the same directories.
+    let again = preopens::get_directories();
+    assert_eq!(again.len(), preopens.len());
+    for ((dir, name), (other, other_name)) in preopens.iter().zip(&again) {
+        assert_eq!(name, other_name);
+        assert!(dir.is_same_object(other).await, "{name} asked for twice");
+    }
+}
+
+async fn test_no_crossing(preopens: &[(Descriptor, String)]) {
+    for (dir, name) in preopens {
+        // The parent of a preopen is off limits, and so are the others.
+        for path in ["..", "../", "../.."] {
+            assert_error_code!(
+                dir.stat_at(PathFlags::empty(), path.to_string())
+                    .await
+                    .unwrap_err(),
+                ErrorCode::NotPermitted
+            );
+        }
+        for (_, other) in preopens.iter().filter(|(_, other)| other != name) {
+            let base = other.trim_start_matches('/');
+            for path in [
+                other.clone(),
+                format!("{other}/{FILE_NAME}"),
+                format!("../{base}/{FILE_NAME}"),
+                format!("./../{base}"),
+            ] {
+                assert_error_code!(
+                    dir.stat_at(PathFlags::SYMLINK_FOLLOW, path.clone())
+                        .await
+                        .unwrap_err(),
+                    ErrorCode::NotPermitted
+                );
+            }
+            assert_error_code!(
+                read_file(dir, &format!("../{base}/{FILE_NAME}"))
+                    .await
+                    .unwrap_err(),
+                ErrorCode::NotPermitted
+            );
+            assert_error_code!(
+                dir.create_directory_at(format!("../{base}/new.cleanup"))
+                    .await
+                    .unwrap_err(),
+                ErrorCode::NotPermitted
+            );
+
+            // Nor can a symlink make the trip.
+            let link = "cross.cleanup";
+            if dir
+                .symlink_at(format!("../{base}"), link.to_string())
+                .await
+                .is_ok()
+            {
+                assert_error_code!(
+                    dir.stat_at(PathFlags::SYMLINK_FOLLOW, link.to_string())
+                        .await
+                        .unwrap_err(),
+                    ErrorCode::NotPermitted
+                );
+                assert_error_code!(
+                    read_file(dir, &format!("{link}/{FILE_NAME}"))
+                        .await
+                        .unwrap_err(),
+                    ErrorCode::NotPermitted
+                );
+                dir.unlink_file_at(link.to_string()).await.unwrap();
+            }
+        }
+    }
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        let expected: Vec<String> = environment::get_arguments().into_iter().skip(1).collect();
+        if expected.len() < 2 {
+            eprintln!("usage: run with two or more open dirs, and their names as arguments");
+            process::exit(1)
+        }
+
+        let preopens = preopens::get_directories();
+        test_names_and_contents(&preopens, &expected).await;
+        test_independent(&preopens).await;
+        test_no_crossing(&preopens).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,331 @@
+use test_wasm32_wasip3::TESTCONFIG;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::filesystem::wasi::clocks::system_clock::{self, Instant};
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, DescriptorStat, DescriptorType, NewTimestamp, OpenFlags, PathFlags,
+};
+use test_wasm32_wasip3::filesystem::{self, create_scratch_dir, pwrite, remove_tree, write_file};
+
+const DIR_NAME: &str = "filesystem-set-times.cleanup";
+
+// No two alike, and none a whole number of seconds, so that truncation or a
+// mixed-up field shows.
+const ATIME: Instant = Instant {
+    seconds: 1_600_000_000,
+    nanoseconds: 123_456_789,
+};
+const MTIME: Instant = Instant {
+    seconds: 1_500_000_000,
+    nanoseconds: 987_654_321,
+};
+const LINK_ATIME: Instant = Instant {
+    seconds: 1_400_000_000,
+    nanoseconds: 111_111_111,
+};
+const LINK_MTIME: Instant = Instant {
+    seconds: 1_300_000_000,
+    nanoseconds: 222_222_222,
+};
+// Times the host picks itself, for `now`, writes and directory changes, come
+// from its own clock, which may be a coarser one that lags `system_clock` by
+// a tick or so.
+const CLOCK_SLACK_NS: i128 = 50_000_000;
+
+fn as_ns(t: &Instant) -> i128 {
+    t.seconds as i128 * 1_000_000_000 + t.nanoseconds as i128
+}
+
+// How finely a timestamp is kept depends on the filesystem underneath, which
+// `TESTCONFIG` describes. Having just been set, the timestamp must be there.
+fn assert_time(actual: Option<Instant>, expected: Instant, what: &str) {
+    let actual = actual.unwrap_or_else(|| panic!("{what} is missing"));
+    let granularity = TESTCONFIG.timestamp_granularity() as i128;
+    assert!(
+        (as_ns(&actual) - as_ns(&expected)).abs() < granularity,
+        "{what} should be {expected:?} (within {granularity}ns); got {actual:?}"
+    );
+}
+
+fn assert_time_between(actual: Option<Instant>, before: &Instant, after: &Instant, what: &str) {
+    let actual = actual.unwrap_or_else(|| panic!("{what} is missing"));
+    let slack = TESTCONFIG.timestamp_granularity() as i128 + CLOCK_SLACK_NS;
+    assert!(
+        as_ns(&actual) + slack > as_ns(before) && as_ns(&actual) < as_ns(after) + slack,
+        "{what} should be between {before:?} and {after:?}; got {actual:?}"
+    );
+}
+
+async fn stat_at(dir: &Descriptor, flags: PathFlags, path: &str) -> DescriptorStat {
+    dir.stat_at(flags, path.to_string()).await.unwrap()
+}
+
+async fn test_set_times(dir: &Descriptor, fd: &Descriptor) {
+    let stat = |flags| stat_at(dir, flags, "file");
+    let nofollow = PathFlags::empty();
+
+    // Explicit values round-trip, as seen through either the descriptor or
+    // the path.
+    fd.set_times(
+        NewTimestamp::Timestamp(ATIME),
+        NewTimestamp::Timestamp(MTIME),
+    )
+    .await
+    .unwrap();
+    for s in [fd.stat().await.unwrap(), stat(nofollow).await] {
+        assert_time(s.data_access_timestamp, ATIME, "atime");
+        assert_time(s.data_modification_timestamp, MTIME, "mtime");
+    }
+
+    // `no-change` leaves one of them alone.
+    fd.set_times(NewTimestamp::NoChange, NewTimestamp::Timestamp(ATIME))
+        .await
+        .unwrap();
+    let s = stat(nofollow).await;
+    assert_time(s.data_access_timestamp, ATIME, "atime after setting mtime");
+    assert_time(s.data_modification_timestamp, ATIME, "mtime");
+    fd.set_times(NewTimestamp::Timestamp(MTIME), NewTimestamp::NoChange)
+        .await
+        .unwrap();
+    let s = stat(nofollow).await;
+    assert_time(s.data_access_timestamp, MTIME, "atime");
+    assert_time(
+        s.data_modification_timestamp,
+        ATIME,
+        "mtime after setting atime",
+    );
+    fd.set_times(NewTimestamp::NoChange, NewTimestamp::NoChange)
+        .await
+        .unwrap();
+    let s = stat(nofollow).await;
+    assert_time(s.data_access_timestamp, MTIME, "atime after no change");
+    assert_time(
+        s.data_modification_timestamp,
+        ATIME,
+        "mtime after no change",
+    );
+
+    // `now` uses the current time.
+    let before = system_clock::now();
+    fd.set_times(NewTimestamp::Now, NewTimestamp::Now)
+        .await
+        .unwrap();
+    let after = system_clock::now();
+    let s = stat(nofollow).await;
+    assert_time_between(s.data_access_timestamp, &before, &after, "atime set to now");
+    assert_time_between(
+        s.data_modification_timestamp,
+        &before,
+        &after,
+        "mtime set to now",
+    );
+
+    // The same by path.
+    dir.set_times_at(
+        nofollow,
+        "file".to_string(),
+        NewTimestamp::Timestamp(ATIME),
+        NewTimestamp::Timestamp(MTIME),
+    )
+    .await
+    .unwrap();
+    let s = fd.stat().await.unwrap();
+    assert_time(s.data_access_timestamp, ATIME, "atime set by path");
+    assert_time(s.data_modification_timestamp, MTIME, "mtime set by path");
+    let before = system_clock::now();
+    dir.set_times_at(
+        nofollow,
+        "file".to_string(),
+        NewTimestamp::NoChange,
+        NewTimestamp::Now,
+    )
+    .await
+    .unwrap();
+    let after = system_clock::now();
+    let s = fd.stat().await.unwrap();
+    assert_time(
+        s.data_access_timestamp,
+        ATIME,
+        "atime after setting mtime by path",
+    );
+    assert_time_between(
+        s.data_modification_timestamp,
+        &before,
+        &after,
+        "mtime set to now by path",
+    );
+}
+
+async fn test_write_updates_mtime(fd: &Descriptor) {
+    fd.set_times(NewTimestamp::NoChange, NewTimestamp::Timestamp(MTIME))
+        .await
+        .unwrap();
+
+    let before = system_clock::now();
+    assert_eq!(pwrite(fd, 0, b"changed").await, Ok(7));
+    let after = system_clock::now();
+    let s = fd.stat().await.unwrap();
+    assert_time_between(
+        s.data_modification_timestamp,
+        &before,
+        &after,
+        "mtime after a write",
+    );
+}
+
+async fn test_directory(dir: &Descriptor) {
+    dir.create_directory_at("subdir".to_string()).await.unwrap();
+    dir.set_times_at(
+        PathFlags::empty(),
+        "subdir".to_string(),
+        NewTimestamp::Timestamp(ATIME),
+        NewTimestamp::Timestamp(MTIME),
+    )
+    .await
+    .unwrap();
+    let s = stat_at(dir, PathFlags::empty(), "subdir").await;
+    assert_eq!(s.type_, DescriptorType::Directory);
+    assert_time(s.data_access_timestamp, ATIME, "directory atime");
+    assert_time(s.data_modification_timestamp, MTIME, "directory mtime");
+
+    let sub = dir
+        .open_at(
+            PathFlags::empty(),
+            "subdir".to_string(),
+            OpenFlags::DIRECTORY,
+            DescriptorFlags::READ | DescriptorFlags::MUTATE_DIRECTORY,
+        )
+        .await
+        .unwrap();
+    sub.set_times(NewTimestamp::Timestamp(MTIME), NewTimestamp::NoChange)
+        .await
+        .unwrap();
+    let s = sub.stat().await.unwrap();
+    assert_time(s.data_access_timestamp, MTIME, "directory atime");
+    assert_time(s.data_modification_timestamp, MTIME, "directory mtime");
+
+    // Adding an entry updates the directory's mtime.
+    let before = system_clock::now();
+    write_file(&sub, "entry", b"").await.unwrap();
+    let after = system_clock::now();
+    let s = sub.stat().await.unwrap();
+    assert_time_between(
+        s.data_modification_timestamp,
+        &before,
+        &after,
+        "mtime after adding an entry",
+    );
+}
+
+async fn test_symlink(dir: &Descriptor) {
+    let nofollow = PathFlags::empty();
+    let follow = PathFlags::SYMLINK_FOLLOW;
+    dir.symlink_at("file".to_string(), "link".to_string())
+        .await
+        .unwrap();
+    dir.set_times_at(
+        nofollow,
+        "file".to_string(),
+        NewTimestamp::Timestamp(ATIME),
+        NewTimestamp::Timestamp(MTIME),
+    )
+    .await
+    .unwrap();
+
+    // By default the times of the link itself are set...
+    dir.set_times_at(
+        nofollow,
+        "link".to_string(),
+        NewTimestamp::Timestamp(LINK_ATIME),
+        NewTimestamp::Timestamp(LINK_MTIME),
+    )
+    .await
+    .unwrap();
+    let s = stat_at(dir, nofollow, "link").await;
+    assert_eq!(s.type_, DescriptorType::SymbolicLink);
+    assert_time(s.data_access_timestamp, LINK_ATIME, "symlink atime");
+    assert_time(s.data_modification_timestamp, LINK_MTIME, "symlink mtime");
+
+    // ...leaving those of its target as they were.
+    let s = stat_at(dir, nofollow, "file").await;
+    assert_time(
+        s.data_access_timestamp,
+        ATIME,
+        "file atime after setting symlink times",
+    );
+    assert_time(
+        s.data_modification_timestamp,
+        MTIME,
+        "file mtime after setting symlink times",
+    );
+
+    // Following the link, it's the other way around.
+    dir.set_times_at(
+        follow,
+        "link".to_string(),
+        NewTimestamp::Timestamp(MTIME),
+        NewTimestamp::Timestamp(ATIME),
+    )
+    .await
+    .unwrap();
+    let s = stat_at(dir, nofollow, "file").await;
+    assert_time(
+        s.data_access_timestamp,
+        MTIME,
+        "file atime set through symlink",
+    );
+    assert_time(
+        s.data_modification_timestamp,
+        ATIME,
+        "file mtime set through symlink",
+    );
+    let s = stat_at(dir, nofollow, "link").await;
+    assert_time(
+        s.data_modification_timestamp,
+        LINK_MTIME,
+        "symlink mtime after following it",
+    );
+
+    // And a stat that follows the link reports the target's times.
+    let s = stat_at(dir, follow, "link").await;
+    assert_eq!(s.type_, DescriptorType::RegularFile);
+    assert_time(s.data_access_timestamp, MTIME, "dereferenced atime");
+    assert_time(s.data_modification_timestamp, ATIME, "dereferenced mtime");
+
+    dir.unlink_file_at("link".to_string()).await.unwrap();
+}
+
+async fn test_filesystem_set_times(root: &Descriptor) {
+    let dir = create_scratch_dir(root, DIR_NAME).await;
+    write_file(&dir, "file", b"contents").await.unwrap();
+    let fd = dir
+        .open_at(
+            PathFlags::empty(),
+            "file".to_string(),
+            OpenFlags::empty(),
+            DescriptorFlags::READ | DescriptorFlags::WRITE,
+        )
+        .await
+        .unwrap();
+
+    test_set_times(&dir, &fd).await;
+    test_write_updates_mtime(&fd).await;
+    drop(fd);
+    test_directory(&dir).await;
+    test_symlink(&dir).await;
+
+    drop(dir);
+    remove_tree(root, DIR_NAME).await;
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_filesystem_set_times(&filesystem::root()).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,337 @@
+use test_wasm32_wasip3::TESTCONFIG;
+use test_wasm32_wasip3::assert_error_code;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, DescriptorType, ErrorCode, OpenFlags, PathFlags,
+};
+use test_wasm32_wasip3::filesystem::{
+    self, Tree, assert_tree, create_scratch_dir, read_file, read_to_eof, remove_tree, write_file,
+};
+
+const DIR_NAME: &str = "filesystem-symlinks.cleanup";
+
+async fn stat_type(
+    dir: &Descriptor,
+    flags: PathFlags,
+    path: &str,
+) -> Result<DescriptorType, ErrorCode> {
+    Ok(dir.stat_at(flags, path.to_string()).await?.type_)
+}
+
+async fn open(dir: &Descriptor, flags: PathFlags, path: &str) -> Result<Descriptor, ErrorCode> {
+    dir.open_at(
+        flags,
+        path.to_string(),
+        OpenFlags::empty(),
+        DescriptorFlags::READ,
+    )
+    .await
+}
+
+async fn test_symlink_at(dir: &Descriptor) {
+    let ln = |target: &str, path: &str| dir.symlink_at(target.to_string(), path.to_string());
+    let readlink = |path: &str| dir.readlink_at(path.to_string());
+
+    ln("file", "file-link").await.unwrap();
+    ln("subdir", "dir-link").await.unwrap();
+    ln("../file", "subdir/up-link").await.unwrap();
+
+    // The target is stored as given, not resolved.
+    assert_eq!(readlink("file-link").await.as_deref(), Ok("file"));
+    assert_eq!(readlink("dir-link").await.as_deref(), Ok("subdir"));
+    assert_eq!(readlink("subdir/up-link").await.as_deref(), Ok("../file"));
+    // Intermediate symlinks are followed; the last one is read.
+    assert_eq!(readlink("dir-link/up-link").await.as_deref(), Ok("../file"));
+
+    // Paths that are taken or missing.
+    assert_error_code!(ln("file", "file-link").await.unwrap_err(), ErrorCode::Exist);
+    assert_error_code!(ln("file", "file").await.unwrap_err(), ErrorCode::Exist);
+    assert_error_code!(ln("file", "subdir").await.unwrap_err(), ErrorCode::Exist);
+    assert_error_code!(ln("file", "").await.unwrap_err(), ErrorCode::NoEntry);
+    assert_error_code!(
+        ln("file", "missing/link").await.unwrap_err(),
+        ErrorCode::NoEntry
+    );
+
+    // Only symlinks can be read.
+    assert_error_code!(readlink("file").await.unwrap_err(), ErrorCode::Invalid);
+    assert_error_code!(readlink("subdir").await.unwrap_err(), ErrorCode::Invalid);
+    assert_error_code!(readlink("missing").await.unwrap_err(), ErrorCode::NoEntry);
+    assert_error_code!(readlink("").await.unwrap_err(), ErrorCode::NoEntry);
+
+    assert_tree(
+        dir,
+        &[
+            ("file", Tree::File(b"hello")),
+            ("file-link", Tree::Symlink("file")),
+            ("dir-link", Tree::Symlink("subdir")),
+            (
+                "subdir",
+                Tree::Dir(&[("up-link", Tree::Symlink("../file"))]),
+            ),
+        ],
+    )
+    .await;
+}
+
+async fn test_stat_at(dir: &Descriptor) {
+    let nofollow = PathFlags::empty();
+    let follow = PathFlags::SYMLINK_FOLLOW;
+
+    // Without SYMLINK_FOLLOW, the link itself is described...
+    assert_eq!(
+        stat_type(dir, nofollow, "file-link").await,
+        Ok(DescriptorType::SymbolicLink)
+    );
+    assert_eq!(
+        stat_type(dir, nofollow, "dir-link").await,
+        Ok(DescriptorType::SymbolicLink)
+    );
+    // ...and with it, whatever the link points to.
+    let file_stat = dir.stat_at(nofollow, "file".to_string()).await.unwrap();
+    let stat = dir.stat_at(follow, "file-link".to_string()).await.unwrap();
+    assert_eq!(stat.type_, DescriptorType::RegularFile);
+    assert_eq!(stat.size, file_stat.size);
+    assert_eq!(
+        stat_type(dir, follow, "dir-link").await,
+        Ok(DescriptorType::Directory)
+    );
+    assert_eq!(
+        stat_type(dir, follow, "subdir/up-link").await,
+        Ok(DescriptorType::RegularFile)
+    );
+
+    // Links in the middle of a path are always followed.
+    assert_eq!(
+        stat_type(dir, nofollow, "dir-link/up-link").await,
+        Ok(DescriptorType::SymbolicLink)
+    );
+    assert_eq!(
+        stat_type(dir, follow, "dir-link/up-link").await,
+        Ok(DescriptorType::RegularFile)
+    );
+
+    // Opening follows the same rules.
+    for path in ["file-link", "dir-link/up-link"] {
+        let fd = open(dir, follow, path).await.unwrap();
+        assert_eq!(&read_to_eof(&fd, 0).await, b"hello", "{path}");
+    }
+    assert_error_code!(
+        read_file(dir, "dir-link/file").await.unwrap_err(),
+        ErrorCode::NoEntry
+    );
+    assert_error_code!(
+        open(dir, nofollow, "file-link").await.unwrap_err(),
+        ErrorCode::Loop,
+        ErrorCode::Access
+    );
+    let sub = dir
+        .open_at(
+            follow,
+            "dir-link".to_string(),
+            OpenFlags::DIRECTORY,
+            DescriptorFlags::READ,
+        )
+        .await
+        .unwrap();
+    assert_eq!(sub.get_type().await, Ok(DescriptorType::Directory));
+    assert_error_code!(
+        dir.open_at(
+            nofollow,
+            "dir-link".to_string(),
+            OpenFlags::DIRECTORY,
+            DescriptorFlags::READ,
+        )
+        .await
+        .unwrap_err(),
+        ErrorCode::Loop,
+        ErrorCode::NotDirectory
+    );
+}
+
+async fn test_loops(dir: &Descriptor) {
+    let ln = |target: &str, path: &str| dir.symlink_at(target.to_string(), path.to_string());
+    let follow = PathFlags::SYMLINK_FOLLOW;
+
+    // A link to itself, and two links to each other.
+    ln("self-loop", "self-loop").await.unwrap();
+    ln("loop-b", "loop-a").await.unwrap();
+    ln("loop-a", "loop-b").await.unwrap();
+
+    for path in ["self-loop", "loop-a", "loop-b"] {
+        assert_eq!(
+            stat_type(dir, PathFlags::empty(), path).await,
+            Ok(DescriptorType::SymbolicLink),
+            "{path}"
+        );
+        assert_error_code!(
+            stat_type(dir, follow, path).await.unwrap_err(),
+            ErrorCode::Loop
+        );
+        assert_error_code!(open(dir, follow, path).await.unwrap_err(), ErrorCode::Loop);
+        assert_error_code!(
+            stat_type(dir, PathFlags::empty(), &format!("{path}/file"))
+                .await
+                .unwrap_err(),
+            ErrorCode::Loop
+        );
+    }
+    assert_eq!(
+        dir.readlink_at("loop-a".to_string()).await.as_deref(),
+        Ok("loop-b")
+    );
+
+    for path in ["self-loop", "loop-a", "loop-b"] {
+        dir.unlink_file_at(path.to_string()).await.unwrap();
+    }
+}
+
+async fn test_dangling(dir: &Descriptor) {
+    let follow = PathFlags::SYMLINK_FOLLOW;
+
+    dir.symlink_at("missing".to_string(), "dangling".to_string())
+        .await
+        .unwrap();
+
+    // The link itself is fine; only following it fails.
+    assert_eq!(
+        dir.readlink_at("dangling".to_string()).await.as_deref(),
+        Ok("missing")
+    );
+    assert_eq!(
+        stat_type(dir, PathFlags::empty(), "dangling").await,
+        Ok(DescriptorType::SymbolicLink)
+    );
+    assert_error_code!(
+        stat_type(dir, follow, "dangling").await.unwrap_err(),
+        ErrorCode::NoEntry
+    );
+    assert_error_code!(
+        open(dir, follow, "dangling").await.unwrap_err(),
+        ErrorCode::NoEntry
+    );
+    assert_error_code!(
+        stat_type(dir, PathFlags::empty(), "dangling/file")
+            .await
+            .unwrap_err(),
+        ErrorCode::NoEntry
+    );
+
+    // Removing the target of a link leaves it dangling.
+    write_file(dir, "target", b"").await.unwrap();
+    dir.symlink_at("target".to_string(), "orphan".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        stat_type(dir, follow, "orphan").await,
+        Ok(DescriptorType::RegularFile)
+    );
+    dir.unlink_file_at("target".to_string()).await.unwrap();
+    assert_error_code!(
+        stat_type(dir, follow, "orphan").await.unwrap_err(),
+        ErrorCode::NoEntry
+    );
+
+    dir.unlink_file_at("dangling".to_string()).await.unwrap();
+    dir.unlink_file_at("orphan".to_string()).await.unwrap();
+}
+
+async fn test_escaping(root: &Descriptor, dir: &Descriptor) {
+    let follow = PathFlags::SYMLINK_FOLLOW;
+
+    // Absolute targets are rejected outright.
+    for target in ["/", "/file"] {
+        assert_error_code!(
+            dir.symlink_at(target.to_string(), "absolute".to_string())
+                .await
+                .unwrap_err(),
+            ErrorCode::NotPermitted
+        );
+    }
+    assert_error_code!(
+        root.symlink_at("file".to_string(), "../outside".to_string())
+            .await
+            .unwrap_err(),
+        ErrorCode::NotPermitted
+    );
+
+    // A relative target that climbs out of the preopen can be created and
+    // read, but never followed.
+    dir.symlink_at("../..".to_string(), "escape".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        dir.readlink_at("escape".to_string()).await.as_deref(),
+        Ok("../..")
+    );
+    assert_eq!(
+        stat_type(dir, PathFlags::empty(), "escape").await,
+        Ok(DescriptorType::SymbolicLink)
+    );
+    assert_error_code!(
+        stat_type(dir, follow, "escape").await.unwrap_err(),
+        ErrorCode::NotPermitted
+    );
+    assert_error_code!(
+        open(dir, follow, "escape").await.unwrap_err(),
+        ErrorCode::NotPermitted
+    );
+    assert_error_code!(
+        stat_type(dir, PathFlags::empty(), "escape/file")
+            .await
+            .unwrap_err(),
+        ErrorCode::NotPermitted
+    );
+    dir.unlink_file_at("escape".to_string()).await.unwrap();
+
+    // Climbing out partway through a path is no better, even when the path
+    // comes back in again.
+    dir.symlink_at(format!("../../{DIR_NAME}/file"), "round-trip".to_string())
+        .await
+        .unwrap();
+    assert_error_code!(
+        stat_type(dir, follow, "round-trip").await.unwrap_err(),
+        ErrorCode::NotPermitted
+    );
+    dir.unlink_file_at("round-trip".to_string()).await.unwrap();
+
+    // Climbing up without leaving the preopen is fine.
+    dir.symlink_at(format!("../{DIR_NAME}/file"), "detour".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        stat_type(dir, follow, "detour").await,
+        Ok(DescriptorType::RegularFile)
+    );
+    dir.unlink_file_at("detour".to_string()).await.unwrap();
+}
+
+async fn test_symlinks(root: &Descriptor) {
+    let dir = create_scratch_dir(root, DIR_NAME).await;
+    write_file(&dir, "file", b"hello").await.unwrap();
+    dir.create_directory_at("subdir".to_string()).await.unwrap();
+
+    test_symlink_at(&dir).await;
+    test_stat_at(&dir).await;
+    if TESTCONFIG.support_dangling_filesystem() {
+        test_loops(&dir).await;
+        test_dangling(&dir).await;
+    }
+    test_escaping(root, &dir).await;
+
+    drop(dir);
+    remove_tree(root, DIR_NAME).await;
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_symlinks(&filesystem::root()).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,163 @@
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::filesystem::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, OpenFlags, PathFlags,
+};
+use test_wasm32_wasip3::filesystem::{
+    self, create_scratch_dir, pappend, pwrite, read_dir_sorted, read_file, remove_tree,
+};
+
+const DIR_NAME: &str = "filesystem-sync.cleanup";
+
+async fn open(
+    dir: &Descriptor,
+    path: &str,
+    oflags: OpenFlags,
+    flags: DescriptorFlags,
+) -> Descriptor {
+    dir.open_at(PathFlags::empty(), path.to_string(), oflags, flags)
+        .await
+        .unwrap()
+}
+
+// Each step goes through a fresh descriptor, so nothing is served from state
+// kept by the one that did the writing.
+async fn assert_contents(dir: &Descriptor, path: &str, expected: &[u8]) {
+    let data = read_file(dir, path).await.unwrap();
+    assert_eq!(data.len(), expected.len(), "size of {path}");
+    assert!(data == expected, "contents of {path}");
+}
+
+async fn test_write_then_sync(dir: &Descriptor) {
+    let fd = open(
+        dir,
+        "written",
+        OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+        DescriptorFlags::READ | DescriptorFlags::WRITE,
+    )
+    .await;
+
+    assert_eq!(pwrite(&fd, 0, b"first line\n").await, Ok(11));
+    fd.sync_data().await.unwrap();
+    assert_contents(dir, "written", b"first line\n").await;
+
+    // Overwriting in place, then syncing data and metadata.
+    assert_eq!(pwrite(&fd, 0, b"FIRST").await, Ok(5));
+    fd.sync().await.unwrap();
+    assert_contents(dir, "written", b"FIRST line\n").await;
+
+    // Writing past the end leaves a hole of zeros.
+    assert_eq!(pwrite(&fd, 16, b"end").await, Ok(3));
+    fd.sync_data().await.unwrap();
+    assert_contents(dir, "written", b"FIRST line\n\0\0\0\0\0end").await;
+
+    // Shrinking is a metadata change that sync has to carry too.
+    fd.set_size(5).await.unwrap();
+    fd.sync().await.unwrap();
+    assert_contents(dir, "written", b"FIRST").await;
+
+    // Syncing again with nothing new to write is harmless.
+    fd.sync().await.unwrap();
+    fd.sync_data().await.unwrap();
+}
+
+async fn test_append_then_sync(dir: &Descriptor) {
+    let fd = open(
+        dir,
+        "appended",
+        OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+        DescriptorFlags::READ | DescriptorFlags::WRITE,
+    )
+    .await;
+
+    let mut expected = Vec::new();
+    for i in 0..10 {
+        let line = format!("record {i}\n");
+        assert_eq!(pappend(&fd, line.as_bytes()).await, Ok(line.len()));
+        expected.extend_from_slice(line.as_bytes());
+        if i % 2 == 0 {
+            fd.sync_data().await.unwrap();
+        } else {
+            fd.sync().await.unwrap();
+        }
+        assert_contents(dir, "appended", &expected).await;
+    }
+
+    // Something too large to go through the stream in one piece.
+    let big: Vec<u8> = (0..1 << 20).map(|i: u32| (i % 251) as u8).collect();
+    assert_eq!(pappend(&fd, &big).await, Ok(big.len()));
+    expected.extend_from_slice(&big);
+    fd.sync().await.unwrap();
+    drop(fd);
+    assert_contents(dir, "appended", &expected).await;
+}
+
+async fn test_read_only(dir: &Descriptor) {
+    // Syncing a descriptor that isn't open for writing succeeds and has no
+    // effect.
+    let fd = open(dir, "written", OpenFlags::empty(), DescriptorFlags::READ).await;
+    fd.sync_data().await.unwrap();
+    fd.sync().await.unwrap();
+    assert_eq!(fd.stat().await.unwrap().size, 5);
+    assert_contents(dir, "written", b"FIRST").await;
+}
+
+async fn test_directory(dir: &Descriptor) {
+    dir.create_directory_at("subdir".to_string()).await.unwrap();
+    let sub = open(
+        dir,
+        "subdir",
+        OpenFlags::DIRECTORY,
+        DescriptorFlags::READ | DescriptorFlags::MUTATE_DIRECTORY,
+    )
+    .await;
+
+    // Syncing a directory makes changes to its entries durable.
+    drop(
+        open(
+            &sub,
+            "entry",
+            OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+            DescriptorFlags::WRITE,
+        )
+        .await,
+    );
+    sub.sync().await.unwrap();
+    sub.sync_data().await.unwrap();
+    dir.sync().await.unwrap();
+
+    // A directory opened only for reading is not open for writing either.
+    let fresh = open(dir, "subdir", OpenFlags::DIRECTORY, DescriptorFlags::READ).await;
+    fresh.sync().await.unwrap();
+    fresh.sync_data().await.unwrap();
+    let names: Vec<_> = read_dir_sorted(&fresh)
+        .await
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(names, ["entry"]);
+}
+
+async fn test_sync(root: &Descriptor) {
+    let dir = create_scratch_dir(root, DIR_NAME).await;
+
+    test_write_then_sync(&dir).await;
+    test_append_then_sync(&dir).await;
+    test_read_only(&dir).await;
+    test_directory(&dir).await;
+
+    drop(dir);
+    remove_tree(root, DIR_NAME).await;
+}
+
+struct Component;
+export!(Component);
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_sync(&filesystem::root()).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::sockets::wasi::sockets::ip_name_lookup::{ErrorCode, resolve_addresses};
+use test_wasm32_wasip3::sockets::wasi::sockets::types::IpAddress;
+
+// Reserved by RFC 6761 so that it never exists, which resolvers may answer
+// without asking the network.
+const MISSING_NAME: &str = "does-not-exist.invalid";
+
+fn from_wasi(addr: IpAddress) -> IpAddr {
+    match addr {
+        IpAddress::Ipv4((a, b, c, d)) => Ipv4Addr::new(a, b, c, d).into(),
+        IpAddress::Ipv6((a, b, c, d, e, f, g, h)) => Ipv6Addr::new(a, b, c, d, e, f, g, h).into(),
+    }
+}
+
+/// Resolves `name`, checking the guarantees every successful lookup makes.
+async fn resolve(name: &str) -> Result<Vec<IpAddr>, ErrorCode> {
+    let addrs: Vec<IpAddr> = resolve_addresses(name.to_string())
+        .await?
+        .into_iter()
+        .map(from_wasi)
+        .collect();
+    assert!(!addrs.is_empty(), "{name} resolved to nothing");
+    for addr in &addrs {
+        if let IpAddr::V6(v6) = addr {
+            assert!(
+                v6.to_ipv4_mapped().is_none(),
+                "{name} resolved to IPv4-mapped {addr}"
+            );
+        }
+    }
+    Ok(addrs)
+}
+
+async fn test_localhost() {
+    let addrs = resolve("localhost").await.unwrap();
+    for addr in &addrs {
+        assert!(addr.is_loopback(), "localhost resolved to {addr}");
+    }
+
+    // Names are not case-sensitive.
+    let upper = resolve("LocalHost").await.unwrap();
+    assert_eq!(
+        upper.into_iter().collect::<BTreeSet<_>>(),
+        addrs.into_iter().collect::<BTreeSet<_>>()
+    );
+}
+
+async fn test_ip_literals() {
+    // Addresses are parsed, not looked up, and come back as they are.
+    for literal in [
+        "0.0.0.0",
+        "127.0.0.1",
+        "192.0.2.33",
+        "255.255.255.255",
+        "::",
+        "::1",
+        "2001:db8::1",
+        "2001:0db8:0000:0000:0000:0000:0000:0001",
+        "DEAD:beef::",
+        "fe80::1",
+    ] {
+        let expected: IpAddr = literal.parse().unwrap();
+        assert_eq!(resolve(literal).await, Ok(vec![expected]), "{literal}");
+    }
+}
+
+async fn test_bracketed() {
+    // A port, or brackets around anything but a whole IPv6 address, make for
+    // a name that is neither a domain name nor an IP address.
+    for name in [
+        "[::1]:80",
+        "[::1",
+        "::1]",
+        "[[::1]]",
+        "[]",
+        "[127.0.0.1]",
+        "[localhost]",
+        "127.0.0.1:80",
+    ] {
+        assert_eq!(
+            resolve(name).await,
+            Err(ErrorCode::InvalidArgument),
+            "{name:?}"
+        );
+    }
+
+    // Brackets around an IPv6 address are how URLs write it, so a host may
+    // accept them, but never for anything other than the address inside.
+    match resolve("[::1]").await {
+        Ok(addrs) => assert_eq!(addrs, [IpAddr::from(Ipv6Addr::LOCALHOST)]),
+        Err(err) => assert_eq!(err, ErrorCode::InvalidArgument),
+    }
+}
+
+async fn test_invalid_names() {
+    let long_label = "a".repeat(64);
+    let long_label_name = format!("{long_label}.com");
+    let long_name = ["abcdefghi"; 26].join(".");
+    assert!(long_name.len() > 253);
+    for name in [
+        "",
+        " ",
+        "exa mple.com",
+        "a.b<&>",
+        "user@example.com",
+        "http://example.com/",
+        "example.com/",
+        long_label.as_str(),
+        long_label_name.as_str(),
+        long_name.as_str(),
+    ] {
+        assert_eq!(
+            resolve(name).await,
+            Err(ErrorCode::InvalidArgument),
+            "{name:?}"
+        );
+    }
+}
+
+async fn test_missing_name() {
+    assert_eq!(
+        resolve(MISSING_NAME).await,
+        Err(ErrorCode::NameUnresolvable)
+    );
+}
+
+async fn test_concurrent() {
+    // Lookups in flight at the same time each get their own answer.
+    let names = [
+        "localhost",
+        "127.0.0.1",
+        "::1",
+        "",
+        "[::1]:80",
+        MISSING_NAME,
+    ];
+    let results = futures::future::join_all(names.map(resolve)).await;
+    assert!(results[0].is_ok());
+    assert_eq!(results[1], Ok(vec![IpAddr::from(Ipv4Addr::LOCALHOST)]));
+    assert_eq!(results[2], Ok(vec![IpAddr::from(Ipv6Addr::LOCALHOST)]));
+    assert_eq!(results[3], Err(ErrorCode::InvalidArgument));
+    assert_eq!(results[4], Err(ErrorCode::InvalidArgument));
+    assert_eq!(results[5], Err(ErrorCode::NameUnresolvable));
+}
+
+struct Component;
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_localhost().await;
+        test_ip_literals().await;
+        test_bracketed().await;
+        test_invalid_names().await;
+        test_missing_name().await;
+        test_concurrent().await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!("main is a stub");
+}
@@ -0,0 +1,160 @@
+use futures::future::join_all;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::sockets::{
+    self,
+    wasi::sockets::types::{ErrorCode, IpAddressFamily, IpSocketAddress, TcpSocket},
+};
+
+// Hundreds of connections in all, made in waves. Both ends of every
+// connection in a wave plus the listener are open at once, which has to fit
+// in macOS's default limit of 256 file descriptors.
+const WAVE: usize = 100;
+const WAVES: usize = 4;
+
+fn payload(i: usize) -> Vec<u8> {
+    format!("connection {i}\n").into_bytes()
+}
+
+async fn write_all(sock: &TcpSocket, data: Vec<u8>) -> Result<(), ErrorCode> {
+    let (mut tx, rx) = sockets::wit_stream::new();
+    let (result, ()) = futures::join!(sock.send(rx), async {
+        let remaining = tx.write_all(data).await;
+        assert!(remaining.is_empty());
+        drop(tx);
+    });
+    result
+}
+
+async fn read_to_end(sock: &TcpSocket) -> Vec<u8> {
+    let (rx, fut) = sock.receive();
+    let data = rx.collect().await;
+    fut.await.unwrap();
+    data
+}
+
+/// Server side: sends back whatever the client sent, once it has sent it
+/// all. Returns what was received and who from.
+async fn echo(sock: TcpSocket) -> (Vec<u8>, IpSocketAddress) {
+    let data = read_to_end(&sock).await;
+    write_all(&sock, data.clone()).await.unwrap();
+    (data, sock.get_remote_address().unwrap())
+}
+
+/// Client side: sends `payload` and returns what comes back.
+async fn exchange(sock: &TcpSocket, payload: Vec<u8>) -> Vec<u8> {
+    let (sent, received) = futures::join!(write_all(sock, payload), read_to_end(sock));
+    sent.unwrap();
+    received
+}
+
+fn test_backlog_size(sock: &TcpSocket) {
+    // Platforms may not support resizing the queue of a listening socket,
+    // but when they do, any non-zero size is fine.
+    for size in [WAVE as u64 * 2, 1, u64::MAX] {
+        let result = sock.set_listen_backlog_size(size);
+        assert!(
+            matches!(result, Ok(()) | Err(ErrorCode::NotSupported)),
+            "bad result for {size}: {result:?}"
+        );
+    }
+    let result = sock.set_listen_backlog_size(0);
+    assert!(
+        matches!(result, Err(ErrorCode::InvalidArgument)),
+        "bad error: {result:?}"
+    );
+    // Leave room for a whole wave, whatever the last successful call was.
+    let _ = sock.set_listen_backlog_size(WAVE as u64);
+}
+
+async fn test_accept_many(family: IpAddressFamily) {
+    let server = TcpSocket::create(family).unwrap();
+    server.bind(IpSocketAddress::localhost(family, 0)).unwrap();
+    server.set_listen_backlog_size(WAVE as u64).unwrap();
+    let mut accept = server.listen().unwrap();
+    test_backlog_size(&server);
+
+    for wave in 0..WAVES {
+        // Numbered across waves, so a connection from an earlier one
+        // turning up again would show.
+        let ids: Vec<usize> = (wave * WAVE..(wave + 1) * WAVE).collect();
+        let clients: Vec<TcpSocket> = ids
+            .iter()
+            .map(|_| TcpSocket::create(family).unwrap())
+            .collect();
+
+        // Every client in the wave connects at once, while the server drains
+        // the stream of incoming connections.
+        let (accepted, connected) = futures::join!(
+            async {
+                let mut accepted = Vec::with_capacity(WAVE);
+                while accepted.len() < WAVE {
+                    accepted.push(accept.next().await.expect("listener stopped accepting"));
+                }
+                accepted
+            },
+            join_all(
+                clients
+                    .iter()
+                    .map(|client| client.connect(server.get_local_address().unwrap()))
+            ),
+        );
+        for (id, result) in ids.iter().zip(connected) {
+            result.unwrap_or_else(|e| panic!("connection {id} failed: {e:?}"));
+        }
+
+        // Then they all talk at once, each over its own connection.
+        let (echoed, replies) = futures::join!(
+            join_all(accepted.into_iter().map(echo)),
+            join_all(
+                ids.iter()
+                    .zip(&clients)
+                    .map(|(&id, client)| exchange(client, payload(id)))
+            ),
+        );
+
+        for (&id, reply) in ids.iter().zip(&replies) {
+            assert_eq!(
+                reply,
+                &payload(id),
+                "reply on connection {id}: {:?}",
+                String::from_utf8_lossy(reply)
+            );
+        }
+
+        // The server saw each payload of the wave exactly once, on the
+        // connection that came from the client which sent it.
+        let mut seen = vec![false; WAVE];
+        for (data, remote) in echoed {
+            let i = (0..WAVE)
+                .find(|&i| data == payload(ids[i]))
+                .unwrap_or_else(|| {
+                    panic!("unexpected payload {:?}", String::from_utf8_lossy(&data))
+                });
+            assert!(!seen[i], "payload {} arrived twice", ids[i]);
+            seen[i] = true;
+            assert_eq!(
+                remote,
+                clients[i].get_local_address().unwrap(),
+                "peer of connection {}",
+                ids[i]
+            );
+        }
+        // Both ends of the wave's connections are closed before the next.
+    }
+}
+
+struct Component;
+
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_accept_many(IpAddressFamily::Ipv4).await;
+        test_accept_many(IpAddressFamily::Ipv6).await;
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!()
+}
@@ -0,0 +1,157 @@
+use std::cell::Cell;
+use test_wasm32_wasip3::Checksum;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest};
+use test_wasm32_wasip3::clocks::{MILLISECOND, wasi::clocks::monotonic_clock};
+use test_wasm32_wasip3::sockets::{
+    self,
+    wasi::sockets::types::{IpAddressFamily, IpSocketAddress, TcpSocket},
+};
+use wit_bindgen::StreamResult;
+
+// Far more than any host should hold on behalf of a reader that isn't reading.
+const TOTAL: usize = 64 * 1024 * 1024;
+const CHUNK: usize = 64 * 1024;
+
+/// A stream of bytes that doesn't repeat within `TOTAL`, so lost, duplicated
+/// or reordered chunks all change the checksum.
+struct Pattern(u64);
+
+impl Pattern {
+    fn new() -> Self {
+        Pattern(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn next_chunk(&mut self, len: usize) -> Vec<u8> {
+        let mut chunk = Vec::with_capacity(len + 8);
+        while chunk.len() < len {
+            // xorshift64
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            chunk.extend_from_slice(&self.0.to_le_bytes());
+        }
+        chunk.truncate(len);
+        chunk
+    }
+}
+
+async fn connected_pair(family: IpAddressFamily) -> (TcpSocket, TcpSocket) {
+    let server = TcpSocket::create(family).unwrap();
+    let client = TcpSocket::create(family).unwrap();
+    server.bind(IpSocketAddress::localhost(family, 0)).unwrap();
+    let mut accept = server.listen().unwrap();
+    let (accepted, ()) = futures::join!(async { accept.next().await.unwrap() }, async {
+        client
+            .connect(server.get_local_address().unwrap())
+            .await
+            .unwrap();
+    });
+    (client, accepted)
+}
+
+/// Writes up to `TOTAL` bytes of the pattern into `tx`, stopping early if the
+/// reading end goes away. Returns how many bytes the stream took and their
+/// checksum; `progress` follows along.
+async fn write_pattern(
+    mut tx: wit_bindgen::StreamWriter<u8>,
+    progress: &Cell<usize>,
+) -> (usize, u64) {
+    let mut pattern = Pattern::new();
+    let mut checksum = Checksum::new();
+    while progress.get() < TOTAL {
+        let chunk = pattern.next_chunk(CHUNK.min(TOTAL - progress.get()));
+        let remaining = tx.write_all(chunk.clone()).await;
+        let taken = chunk.len() - remaining.len();
+        checksum.update(&chunk[..taken]);
+        progress.set(progress.get() + taken);
+        if !remaining.is_empty() {
+            break;
+        }
+    }
+    drop(tx);
+    (progress.get(), checksum.0)
+}
+
+/// Waits until the writer stops making progress, which it must do long
+/// before `TOTAL` while nobody reads.
+async fn wait_for_stall(progress: &Cell<usize>) {
+    let mut last = None;
+    while last != Some(progress.get()) && progress.get() < TOTAL {
+        last = Some(progress.get());
+        monotonic_clock::wait_for(100 * MILLISECOND).await;
+    }
+}
+
+async fn test_backpressure(family: IpAddressFamily) {
+    let (client, server) = connected_pair(family).await;
+    let (tx, rx) = sockets::wit_stream::new();
+    let (mut server_rx, server_fut) = server.receive();
+    let progress = Cell::new(0);
+    let send_done = Cell::new(false);
+
+    let ((), (sent, sent_checksum), (received, received_checksum)) = futures::join!(
+        async {
+            client.send(rx).await.unwrap();
+            // Only once the stream has ended and all of it was taken.
+            assert_eq!(progress.get(), TOTAL, "send finished early");
+            send_done.set(true);
+        },
+        write_pattern(tx, &progress),
+        async {
+            // Stall the reader. The host may buffer some data, but sooner or
+            // later the writer has to wait, and the send can't be done.
+            wait_for_stall(&progress).await;
+            let stalled_at = progress.get();
+            assert!(
+                stalled_at < TOTAL,
+                "all {TOTAL} bytes were taken while the reader stalled"
+            );
+            assert!(!send_done.get(), "send finished while the reader stalled");
+
+            let mut checksum = Checksum::new();
+            let mut received = 0;
+            loop {
+                let (result, chunk) = server_rx.read(Vec::with_capacity(CHUNK)).await;
+                match result {
+                    StreamResult::Complete(_) => {
+                        checksum.update(&chunk);
+                        received += chunk.len();
+                    }
+                    StreamResult::Dropped => break,
+                    StreamResult::Cancelled => panic!("who cancelled the stream?"),
+                }
+            }
+            (received, checksum.0)
+        }
+    );
+    drop(server_rx);
+    server_fut.await.unwrap();
+
+    assert!(send_done.get());
+    assert_eq!(sent, TOTAL);
+    assert_eq!(received, TOTAL);
+    assert_eq!(
+        received_checksum, sent_checksum,
+        "checksum of the received data"
+    );
+}
+
+struct Component;
+
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        test_backpressure(IpAddressFamily::Ipv4).await;
+        test_backpressure(IpAddressFamily::Ipv6).await;
+        // Dropping the receive stream early is `shutdown(SHUT_RD)`, which
+        // `wasi:sockets` doesn't promise the peer will notice, so there is
+        // nothing to check about it here. Aborted connections are covered by
+        // sockets-tcp-shutdown.
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!()
+}
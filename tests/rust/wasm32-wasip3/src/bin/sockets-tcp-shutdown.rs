@@ -0,0 +1,175 @@
+use test_wasm32_wasip3::assert_error_code;
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest, wasi::cli::environment};
+use test_wasm32_wasip3::sockets::{
+    self,
+    wasi::sockets::types::{ErrorCode, IpAddress, IpAddressFamily, IpSocketAddress, TcpSocket},
+};
+use wit_bindgen::StreamResult;
+
+// More than the receiving end reads before it goes away, so that some of it
+// is still waiting there when it does.
+const UNREAD: usize = 1024 * 1024;
+const CHUNK: usize = 4 * 1024;
+
+async fn connected_pair(family: IpAddressFamily) -> (TcpSocket, TcpSocket) {
+    let server = TcpSocket::create(family).unwrap();
+    let client = TcpSocket::create(family).unwrap();
+    server.bind(IpSocketAddress::localhost(family, 0)).unwrap();
+    let mut accept = server.listen().unwrap();
+    let (accepted, ()) = futures::join!(async { accept.next().await.unwrap() }, async {
+        client
+            .connect(server.get_local_address().unwrap())
+            .await
+            .unwrap();
+    });
+    (client, accepted)
+}
+
+/// Sends `data`, then ends the stream, which shuts down the sending side
+/// of the connection only.
+async fn write_all(sock: &TcpSocket, data: Vec<u8>) -> Result<(), ErrorCode> {
+    let (mut tx, rx) = sockets::wit_stream::new();
+    let (result, ()) = futures::join!(sock.send(rx), async {
+        let remaining = tx.write_all(data).await;
+        assert!(remaining.is_empty());
+        drop(tx);
+    });
+    result
+}
+
+/// Like `write_all`, but the peer may go away partway through, in which
+/// case the stream takes only some of `data`.
+async fn write_some(sock: &TcpSocket, data: Vec<u8>) -> Result<(), ErrorCode> {
+    let (mut tx, rx) = sockets::wit_stream::new();
+    let len = data.len();
+    let (result, ()) = futures::join!(sock.send(rx), async {
+        let remaining = tx.write_all(data).await;
+        assert!(remaining.len() < len, "the stream took none of {len} bytes");
+        drop(tx);
+    });
+    result
+}
+
+/// Reads until the peer stops sending, which must be a clean end.
+async fn read_to_end(sock: &TcpSocket) -> Vec<u8> {
+    let (rx, fut) = sock.receive();
+    let data = rx.collect().await;
+    fut.await.unwrap();
+    data
+}
+
+/// Reads until the peer aborts the connection, which must not look like a
+/// clean end.
+async fn read_to_reset(sock: &TcpSocket) {
+    let (rx, fut) = sock.receive();
+    rx.collect().await;
+    let result = fut.await;
+    match result {
+        Err(err) => {
+            assert_error_code!(
+                err,
+                ErrorCode::ConnectionReset,
+                ErrorCode::ConnectionAborted
+            );
+        }
+        Ok(()) => panic!("an aborted connection ended cleanly"),
+    }
+}
+
+async fn test_half_close(family: IpAddressFamily) {
+    let (client, server) = connected_pair(family).await;
+
+    // The client is done talking once its send completes, while the server
+    // still has something to say.
+    let ((), ()) = futures::join!(
+        async {
+            write_all(&client, b"request".to_vec()).await.unwrap();
+            assert_eq!(read_to_end(&client).await, b"response");
+        },
+        async {
+            assert_eq!(read_to_end(&server).await, b"request");
+            write_all(&server, b"response".to_vec()).await.unwrap();
+        },
+    );
+}
+
+async fn test_abortive_close(family: IpAddressFamily) {
+    let (client, server) = connected_pair(family).await;
+    let (mut client_rx, client_fut) = client.receive();
+
+    let (send_result, ()) = futures::join!(write_some(&server, vec![0x5a; UNREAD]), async {
+        let (result, chunk) = client_rx.read(Vec::with_capacity(CHUNK)).await;
+        assert!(
+            matches!(result, StreamResult::Complete(_)),
+            "stream ended early: {result:?}"
+        );
+        assert!(!chunk.is_empty());
+        // Going away with data left unread aborts the connection rather
+        // than closing it.
+        drop(client_rx);
+        client_fut.await.unwrap();
+        drop(client);
+    });
+
+    // Whatever the sender managed to hand over before then, the server must
+    // hear about the abort, rather than see the end of the client's data.
+    if let Err(err) = send_result {
+        assert_error_code!(
+            err,
+            ErrorCode::ConnectionReset,
+            ErrorCode::ConnectionAborted,
+            ErrorCode::ConnectionBroken
+        );
+    }
+    read_to_reset(&server).await;
+}
+
+/// Lets the test runner play the client, one connection per `connect`. The
+/// first is shut down for writing and then closed, the second reset.
+async fn serve_runner() {
+    let server = TcpSocket::create(IpAddressFamily::Ipv4).unwrap();
+    server
+        .bind(IpSocketAddress::localhost(IpAddressFamily::Ipv4, 0))
+        .unwrap();
+    let mut accept = server.listen().unwrap();
+    let addr = server.get_local_address().unwrap();
+    let IpAddress::Ipv4((a, b, c, d)) = addr.ip_addr() else {
+        unreachable!()
+    };
+    let addr = format!("{a}.{b}.{c}.{d}:{}", addr.port());
+
+    println!("{addr}");
+    let sock = accept.next().await.unwrap();
+    assert_eq!(read_to_end(&sock).await, b"hello");
+    write_all(&sock, b"bye".to_vec()).await.unwrap();
+    drop(sock);
+
+    println!("{addr}");
+    let sock = accept.next().await.unwrap();
+    read_to_reset(&sock).await;
+}
+
+struct Component;
+
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        let args = environment::get_arguments();
+        match args.get(1).map(String::as_str) {
+            None => {
+                test_half_close(IpAddressFamily::Ipv4).await;
+                test_half_close(IpAddressFamily::Ipv6).await;
+                test_abortive_close(IpAddressFamily::Ipv4).await;
+                test_abortive_close(IpAddressFamily::Ipv6).await;
+            }
+            Some("peer") => serve_runner().await,
+            Some(arg) => panic!("unknown argument {arg:?}"),
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!()
+}
@@ -0,0 +1,138 @@
+use test_wasm32_wasip3::cli::{export, exports::wasi::cli::run::Guest, wasi::cli::environment};
+use test_wasm32_wasip3::sockets::wasi::sockets::types::{
+    ErrorCode, IpAddress, IpAddressFamily, IpSocketAddress, UdpSocket,
+};
+
+// How many `connect` operations the runner makes, each from a port of its own.
+const PEERS: usize = 4;
+// Room for the largest datagram on either end. macOS in particular won't send
+// one bigger than the send buffer.
+const BUFFER_SIZE: u64 = 128 * 1024;
+
+/// The largest datagram each family can carry: 65535 bytes less the IPv4
+/// and UDP headers, or less just the UDP header for IPv6, whose header
+/// doesn't count towards the payload length.
+fn max_datagram_size(family: IpAddressFamily) -> usize {
+    match family {
+        IpAddressFamily::Ipv4 => 65535 - 20 - 8,
+        IpAddressFamily::Ipv6 => 65535 - 8,
+    }
+}
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+async fn test_large_datagrams(family: IpAddressFamily) {
+    let server = UdpSocket::create(family).unwrap();
+    let client = UdpSocket::create(family).unwrap();
+    server.bind(IpSocketAddress::localhost(family, 0)).unwrap();
+    client.connect(server.get_local_address().unwrap()).unwrap();
+    server.set_receive_buffer_size(BUFFER_SIZE).unwrap();
+    client.set_send_buffer_size(BUFFER_SIZE).unwrap();
+
+    // One at a time, so none has to wait in a receive buffer that may not
+    // have room for several of them.
+    let max = max_datagram_size(family);
+    for len in [max, max - 1, 60 * 1024, 1, 0, max] {
+        let data = pattern(len);
+        let (received, sent) = futures::join!(server.receive(), client.send(data.clone(), None));
+        sent.unwrap_or_else(|e| panic!("sending {len} bytes: {e:?}"));
+        let (received, sender) = received.unwrap();
+        // All of it at once, however the network had to split it up.
+        assert_eq!(received.len(), len, "length of a {len} byte datagram");
+        assert!(received == data, "contents of a {len} byte datagram");
+        assert_eq!(sender, client.get_local_address().unwrap());
+    }
+}
+
+/// Receives a datagram and sends it straight back to whoever sent it, so
+/// that it only reaches the runner if the sender was reported correctly.
+async fn echo(sock: &UdpSocket) -> (Vec<u8>, IpSocketAddress) {
+    let (data, sender) = sock.receive().await.unwrap();
+    sock.send(data.clone(), Some(sender)).await.unwrap();
+    (data, sender)
+}
+
+/// Lets the test runner play several peers, one per `connect`. Each sends
+/// a datagram in turn, then the socket is connected to the first of them
+/// and later disconnected again, when the last one, which kept quiet in the
+/// meantime, gets through.
+async fn serve_runner() {
+    let sock = UdpSocket::create(IpAddressFamily::Ipv4).unwrap();
+    sock.bind(IpSocketAddress::localhost(IpAddressFamily::Ipv4, 0))
+        .unwrap();
+    let addr = sock.get_local_address().unwrap();
+    let IpAddress::Ipv4((a, b, c, d)) = addr.ip_addr() else {
+        unreachable!()
+    };
+    for _ in 0..PEERS {
+        println!("{a}.{b}.{c}.{d}:{}", addr.port());
+    }
+
+    let mut peers = Vec::with_capacity(PEERS);
+    for _ in 0..PEERS {
+        let (data, sender) = echo(&sock).await;
+        assert!(!peers.contains(&sender), "{data:?} came from a known peer");
+        peers.push(sender);
+    }
+
+    // Once connected, only the first peer gets through, and it's the only
+    // one the socket can send to.
+    sock.connect(peers[0]).unwrap();
+    assert_eq!(sock.get_remote_address(), Ok(peers[0]));
+    assert_eq!(
+        sock.send(b"nope".to_vec(), Some(peers[1])).await,
+        Err(ErrorCode::InvalidArgument)
+    );
+    // Tell the runner to go ahead, then expect only what the first peer
+    // sent after the others did.
+    sock.send(b"connected".to_vec(), None).await.unwrap();
+    let (data, sender) = sock.receive().await.unwrap();
+    assert_eq!(data, b"kept", "{:?}", String::from_utf8_lossy(&data));
+    assert_eq!(sender, peers[0]);
+    sock.send(data, Some(sender)).await.unwrap();
+
+    // Disconnected, everyone gets through again, and sending needs an
+    // address.
+    sock.disconnect().unwrap();
+    assert_eq!(sock.get_remote_address(), Err(ErrorCode::InvalidState));
+    assert_eq!(
+        sock.send(b"nope".to_vec(), None).await,
+        Err(ErrorCode::InvalidArgument)
+    );
+    sock.send(b"disconnected".to_vec(), Some(peers[0]))
+        .await
+        .unwrap();
+    let (data, sender) = echo(&sock).await;
+    assert_eq!(
+        data,
+        b"welcome back",
+        "{:?}",
+        String::from_utf8_lossy(&data)
+    );
+    assert_eq!(sender, peers[3]);
+}
+
+struct Component;
+
+export!(Component);
+
+impl Guest for Component {
+    async fn run() -> Result<(), ()> {
+        let args = environment::get_arguments();
+        match args.get(1).map(String::as_str) {
+            None => {
+                test_large_datagrams(IpAddressFamily::Ipv4).await;
+                test_large_datagrams(IpAddressFamily::Ipv6).await;
+            }
+            Some("peer") => serve_runner().await,
+            Some(arg) => panic!("unknown argument {arg:?}"),
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    unreachable!()
+}
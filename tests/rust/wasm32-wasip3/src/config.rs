@@ -0,0 +1,75 @@
+use crate::cli::wasi::cli::environment;
+
+pub struct TestConfig {
+    errno_mode: ErrnoMode,
+    no_dangling_filesystem: bool,
+    no_rename_dir_to_empty_dir: bool,
+    timestamp_granularity_ns: u64,
+}
+
+enum ErrnoMode {
+    Unix,
+    MacOS,
+    Windows,
+    Permissive,
+}
+
+impl TestConfig {
+    pub fn from_env() -> Self {
+        let env = environment::get_environment();
+        let is_set = |name: &str| env.iter().any(|(key, _)| key == name);
+        let get = |name: &str| env.iter().find(|(key, _)| key == name).map(|(_, v)| v);
+
+        let errno_mode = if is_set("ERRNO_MODE_UNIX") {
+            ErrnoMode::Unix
+        } else if is_set("ERRNO_MODE_MACOS") {
+            ErrnoMode::MacOS
+        } else if is_set("ERRNO_MODE_WINDOWS") {
+            ErrnoMode::Windows
+        } else {
+            ErrnoMode::Permissive
+        };
+        let no_dangling_filesystem = is_set("NO_DANGLING_FILESYSTEM");
+        let no_rename_dir_to_empty_dir = is_set("NO_RENAME_DIR_TO_EMPTY_DIR");
+        let timestamp_granularity_ns = match get("TIMESTAMP_GRANULARITY_NS") {
+            Some(ns) => ns
+                .parse()
+                .expect("TIMESTAMP_GRANULARITY_NS should be a number of nanoseconds"),
+            None => 1,
+        };
+
+        TestConfig {
+            errno_mode,
+            no_dangling_filesystem,
+            no_rename_dir_to_empty_dir,
+            timestamp_granularity_ns,
+        }
+    }
+    pub fn errno_expect_unix(&self) -> bool {
+        match self.errno_mode {
+            ErrnoMode::Unix | ErrnoMode::MacOS => true,
+            _ => false,
+        }
+    }
+    pub fn errno_expect_macos(&self) -> bool {
+        match self.errno_mode {
+            ErrnoMode::MacOS => true,
+            _ => false,
+        }
+    }
+    pub fn errno_expect_windows(&self) -> bool {
+        match self.errno_mode {
+            ErrnoMode::Windows => true,
+            _ => false,
+        }
+    }
+    pub fn support_dangling_filesystem(&self) -> bool {
+        !self.no_dangling_filesystem
+    }
+    pub fn support_rename_dir_to_empty_dir(&self) -> bool {
+        !self.no_rename_dir_to_empty_dir
+    }
+    pub fn timestamp_granularity(&self) -> u64 {
+        self.timestamp_granularity_ns.max(1)
+    }
+}
@@ -0,0 +1,310 @@
+wit_bindgen::generate!({
+    inline: r"
+	package wasi-testsuite:test;
+
+	world filesystem-test {
+		include wasi:filesystem/imports@0.3.0;
+	}
+    ",
+    features:["clocks-timezone"],
+    pub_export_macro: true,
+    default_bindings_module: "test_wasm32_wasip3::filesystem",
+    additional_derives: [PartialEq, Eq, Hash, Clone],
+    generate_all
+});
+
+use std::process;
+
+use futures::join;
+use wit_bindgen::StreamResult;
+
+use self::wasi::filesystem::preopens;
+use self::wasi::filesystem::types::{
+    Descriptor, DescriptorFlags, DescriptorType, DirectoryEntry, ErrorCode, OpenFlags, PathFlags,
+};
+
+/// Returns the only preopened directory, exiting if the runner mapped any
+/// other number of directories.
+pub fn root() -> Descriptor {
+    let mut dirs = preopens::get_directories();
+    if dirs.len() != 1 {
+        eprintln!("usage: run with one open dir");
+        process::exit(1)
+    }
+    dirs.pop().unwrap().0
+}
+
+/// Returns the preopened directory whose guest path is `name`.
+pub fn preopen(name: &str) -> Option<Descriptor> {
+    preopens::get_directories()
+        .into_iter()
+        .find(|(_, path)| path == name)
+        .map(|(dir, _)| dir)
+}
+
+/// Creates the directory `name` in `dir` and opens it for reading and
+/// mutation. Use a `.cleanup` name so that the runner removes leftovers.
+pub async fn create_scratch_dir(dir: &Descriptor, name: &str) -> Descriptor {
+    dir.create_directory_at(name.to_string()).await.unwrap();
+    dir.open_at(
+        PathFlags::empty(),
+        name.to_string(),
+        OpenFlags::DIRECTORY,
+        DescriptorFlags::READ | DescriptorFlags::MUTATE_DIRECTORY,
+    )
+    .await
+    .unwrap()
+}
+
+/// Removes `name` from `dir`, recursing into directories but never through
+/// symlinks.
+pub async fn remove_tree(dir: &Descriptor, name: &str) {
+    let stat = dir
+        .stat_at(PathFlags::empty(), name.to_string())
+        .await
+        .unwrap();
+    if stat.type_ != DescriptorType::Directory {
+        dir.unlink_file_at(name.to_string()).await.unwrap();
+        return;
+    }
+
+    let sub = dir
+        .open_at(
+            PathFlags::empty(),
+            name.to_string(),
+            OpenFlags::DIRECTORY,
+            DescriptorFlags::READ | DescriptorFlags::MUTATE_DIRECTORY,
+        )
+        .await
+        .unwrap();
+    for entry in read_dir_sorted(&sub).await {
+        Box::pin(remove_tree(&sub, &entry.name)).await;
+    }
+    drop(sub);
+    dir.remove_directory_at(name.to_string()).await.unwrap();
+}
+
+/// Reads up to `size` bytes at `offset`, checking that the stream never hands
+/// back more than was asked for.
+pub async fn pread(fd: &Descriptor, size: usize, offset: u64) -> Result<Vec<u8>, ErrorCode> {
+    let (mut rx, future) = fd.read_via_stream(offset);
+    let data = Vec::<u8>::with_capacity(size);
+    let mut bytes_read = 0;
+    let (mut result, mut data) = rx.read(data).await;
+    loop {
+        match result {
+            StreamResult::Complete(n) => {
+                assert!(n <= size - bytes_read);
+                bytes_read += n;
+                assert_eq!(data.len(), bytes_read);
+                if bytes_read == size {
+                    break;
+                }
+                (result, data) = rx.read(data).await;
+            }
+            StreamResult::Dropped => {
+                // https://github.com/bytecodealliance/wit-bindgen/issues/1396
+                assert!(data.len() >= bytes_read);
+                break;
+            }
+            StreamResult::Cancelled => {
+                panic!("who cancelled the stream?");
+            }
+        }
+    }
+    drop(rx);
+    match future.await {
+        Ok(()) => Ok(data),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes all of `data` at `offset`, returning how many bytes the host took.
+pub async fn pwrite(fd: &Descriptor, offset: u64, data: &[u8]) -> Result<usize, ErrorCode> {
+    let (mut tx, rx) = wit_stream::new();
+    let future = fd.write_via_stream(rx, offset);
+    let len = data.len();
+    let mut written: usize = 0;
+    let mut result: Result<(), ErrorCode> = Ok(());
+    join! {
+        async {
+            let (mut result, mut buf) = tx.write(data.to_vec()).await;
+            loop {
+                match result {
+                    StreamResult::Complete(n) => {
+                        assert!(n <= len - written);
+                        written += n;
+                        assert_eq!(buf.remaining(), len - written);
+                        if buf.remaining() != 0 {
+                            (result, buf) = tx.write_buf(buf).await;
+                        } else {
+                            break;
+                        }
+                    }
+                    StreamResult::Dropped => {
+                        // https://github.com/bytecodealliance/wit-bindgen/issues/1396
+                        assert!(buf.remaining() <= len - written);
+                        panic!("receiver dropped the stream?");
+                    }
+                    StreamResult::Cancelled => {
+                        break;
+                    }
+                }
+            }
+            assert_eq!(buf.remaining(), len - written);
+            drop(tx);
+        },
+        async { result = future.await; }
+    };
+    match result {
+        Ok(()) => Ok(written),
+        Err(err) => Err(err),
+    }
+}
+
+/// Appends all of `data`, checking that the file grew by exactly the number
+/// of bytes written.
+pub async fn pappend(fd: &Descriptor, data: &[u8]) -> Result<usize, ErrorCode> {
+    let (mut tx, rx) = wit_stream::new();
+    let future = fd.append_via_stream(rx);
+    let initial_size = fd.stat().await.unwrap().size as usize;
+    let len = data.len();
+    let mut written: usize = 0;
+    let mut result: Result<(), ErrorCode> = Ok(());
+    join! {
+        async {
+            let (mut result, mut buf) = tx.write(data.to_vec()).await;
+            loop {
+                match result {
+                    StreamResult::Complete(n) => {
+                        assert!(n <= len - written);
+                        written += n;
+                        assert_eq!(buf.remaining(), len - written);
+                        if buf.remaining() != 0 {
+                            (result, buf) = tx.write_buf(buf).await;
+                        } else {
+                            break;
+                        }
+                    }
+                    StreamResult::Dropped => {
+                        panic!("receiver dropped the stream?");
+                    }
+                    StreamResult::Cancelled => {
+                        break;
+                    }
+                }
+            }
+            assert_eq!(buf.remaining(), len - written);
+            drop(tx);
+        },
+        async { result = future.await; }
+    };
+    match result {
+        Ok(()) => {
+            assert_eq!(
+                fd.stat().await.unwrap().size as usize,
+                initial_size + written
+            );
+            Ok(written)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub async fn read_to_eof(fd: &Descriptor, offset: u64) -> Vec<u8> {
+    let (stream, success) = fd.read_via_stream(offset);
+    let ret = stream.collect().await;
+    success.await.unwrap();
+    ret
+}
+
+/// Opens `path` in `dir` and reads the whole file.
+pub async fn read_file(dir: &Descriptor, path: &str) -> Result<Vec<u8>, ErrorCode> {
+    let fd = dir
+        .open_at(
+            PathFlags::empty(),
+            path.to_string(),
+            OpenFlags::empty(),
+            DescriptorFlags::READ,
+        )
+        .await?;
+    let (stream, success) = fd.read_via_stream(0);
+    let data = stream.collect().await;
+    success.await?;
+    Ok(data)
+}
+
+/// Creates or truncates `path` in `dir` and writes `data` to it.
+pub async fn write_file(dir: &Descriptor, path: &str, data: &[u8]) -> Result<(), ErrorCode> {
+    let fd = dir
+        .open_at(
+            PathFlags::empty(),
+            path.to_string(),
+            OpenFlags::CREATE | OpenFlags::TRUNCATE,
+            DescriptorFlags::READ | DescriptorFlags::WRITE,
+        )
+        .await?;
+    let written = pwrite(&fd, 0, data).await?;
+    assert_eq!(written, data.len());
+    Ok(())
+}
+
+pub async fn read_dir_sorted(dir: &Descriptor) -> Vec<DirectoryEntry> {
+    let (stream, result) = dir.read_directory();
+    let mut entries = stream.collect().await;
+    result.await.unwrap();
+    entries.sort_by_key(|e| e.name.clone());
+    entries
+}
+
+/// Expected shape of one entry in `assert_tree`.
+pub enum Tree<'a> {
+    File(&'a [u8]),
+    Dir(&'a [(&'a str, Tree<'a>)]),
+    Symlink(&'a str),
+}
+
+/// Asserts that `dir` holds exactly the entries in `expected`, comparing file
+/// contents, symlink targets and subdirectories recursively.
+pub async fn assert_tree(dir: &Descriptor, expected: &[(&str, Tree<'_>)]) {
+    let mut expected: Vec<_> = expected.iter().collect();
+    expected.sort_by_key(|(name, _)| *name);
+    let entries = read_dir_sorted(dir).await;
+    let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+    let expected_names: Vec<_> = expected.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, expected_names);
+
+    for (entry, (name, tree)) in entries.iter().zip(expected) {
+        match tree {
+            Tree::File(contents) => {
+                assert_eq!(entry.type_, DescriptorType::RegularFile, "{name}");
+                assert_eq!(
+                    &read_file(dir, name).await.unwrap(),
+                    contents,
+                    "contents of {name}"
+                );
+            }
+            Tree::Dir(children) => {
+                assert_eq!(entry.type_, DescriptorType::Directory, "{name}");
+                let sub = dir
+                    .open_at(
+                        PathFlags::empty(),
+                        name.to_string(),
+                        OpenFlags::DIRECTORY,
+                        DescriptorFlags::READ,
+                    )
+                    .await
+                    .unwrap();
+                Box::pin(assert_tree(&sub, children)).await;
+            }
+            Tree::Symlink(target) => {
+                assert_eq!(entry.type_, DescriptorType::SymbolicLink, "{name}");
+                assert_eq!(
+                    dir.readlink_at(name.to_string()).await.as_deref(),
+                    Ok(*target),
+                    "target of {name}"
+                );
+            }
+        }
+    }
+}
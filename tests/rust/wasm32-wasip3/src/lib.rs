@@ -1,5 +1,104 @@
 pub mod cli;
 pub mod clocks;
+pub mod config;
+pub mod filesystem;
 pub mod http;
 pub mod random;
 pub mod sockets;
+
+use std::sync::LazyLock;
+
+pub static TESTCONFIG: LazyLock<config::TestConfig> = LazyLock::new(config::TestConfig::from_env);
+
+/// Asserts that an `ErrorCode` from `wasi:filesystem` or `wasi:sockets` is one
+/// of the given alternatives. As with `assert_errno!` in the wasip1 crate,
+/// alternatives can be tagged `unix =>`, `macos =>` or `windows =>` so that an
+/// `ERRNO_MODE_*` environment variable selects the one the host must produce;
+/// without one, any of them is accepted.
+#[macro_export]
+macro_rules! assert_error_code {
+    ($s:expr, windows => $i:expr, $( $rest:tt )+) => {
+        let e = $s;
+        if $crate::TESTCONFIG.errno_expect_windows() {
+            $crate::assert_error_code!(e, $i);
+        } else {
+            $crate::assert_error_code!(e, $($rest)+, $i);
+        }
+    };
+    ($s:expr, macos => $i:expr, $( $rest:tt )+) => {
+        let e = $s;
+        if $crate::TESTCONFIG.errno_expect_macos() {
+            $crate::assert_error_code!(e, $i);
+        } else {
+            $crate::assert_error_code!(e, $($rest)+, $i);
+        }
+    };
+    ($s:expr, unix => $i:expr, $( $rest:tt )+) => {
+        let e = $s;
+        if $crate::TESTCONFIG.errno_expect_unix() {
+            $crate::assert_error_code!(e, $i);
+        } else {
+            $crate::assert_error_code!(e, $($rest)+, $i);
+        }
+    };
+    ($s:expr, $( $i:expr ),+) => {
+        let e = $s;
+        {
+            // Pretty printing infrastructure
+            struct Alt<'a>(&'a [String]);
+            impl<'a> std::fmt::Display for Alt<'a> {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    let l = self.0.len();
+                    if l == 0 {
+                        unreachable!()
+                    } else if l == 1 {
+                        f.write_str(&self.0[0])
+                    } else if l == 2 {
+                        f.write_str(&self.0[0])?;
+                        f.write_str(" or ")?;
+                        f.write_str(&self.0[1])
+                    } else {
+                        for (ix, s) in self.0.iter().enumerate() {
+                            if ix == l - 1 {
+                                f.write_str("or ")?;
+                                f.write_str(s)?;
+                            } else {
+                                f.write_str(s)?;
+                                f.write_str(", ")?;
+                            }
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            assert!( $( e == $i || )+ false,
+                "expected error code {}; got {:?}",
+                Alt(&[ $( format!("{:?}", $i) ),+ ]),
+                e,
+            )
+        }
+    };
+}
+
+/// 64-bit FNV-1a, for comparing more data than a test wants to keep around
+/// by updating it a chunk at a time.
+pub struct Checksum(pub u64);
+
+impl Checksum {
+    pub fn new() -> Self {
+        Checksum(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self::new()
+    }
+}